@@ -13,6 +13,8 @@ pub use primitives::*;
 pub use metadata::*;
 
 pub const ID_ANNOTATIONS: [u8; 4] = [b'.', b'-', b'_', b'\0'];
+/// prefix of the symbols kept for pool share assets, followed by the pool id
+pub const SHARE_SYMBOL_PREFIX: &[u8] = b"LP-";
 
 pub fn is_valid_id(id: &FixedString) -> bool {
 	if ID_ANNOTATIONS.contains(&id.0[0]){
@@ -31,6 +33,11 @@ pub fn is_valid_symbol(name: &AssetName) -> bool {
 	is_valid_id(name)
 }
 
+#[inline]
+pub fn is_share_symbol(symbol: &AssetSymbol) -> bool {
+	symbol.0.starts_with(SHARE_SYMBOL_PREFIX)
+}
+
 #[inline]
 pub fn is_valid_name(name: &AssetName) -> bool {
 	name.is_empty() || is_valid_id(name)
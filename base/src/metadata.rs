@@ -8,12 +8,14 @@ use codec::{Decode, Encode};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_std::collections::btree_map::BTreeMap;
+use sp_std::vec::Vec;
 
 use crate::primitives::*;
 
 pub type SymbolData = BTreeMap<AssetSymbol, Balance>;
-pub type SharesData<AccountId> = BTreeMap<AccountId, Balance>;
 pub type VolumeData = BTreeMap<AssetSymbol, SwapVolume>;
+/// hops of a swap route, each hop swaps into the symbol through the pool
+pub type SwapPath = Vec<(PoolId, AssetSymbol)>;
 
 
 pub const DEFAULT_SYMBOL: AssetSymbol = FixedString::from_const_string("PEX");
@@ -31,6 +33,8 @@ pub struct AssetInfo {
 	pub is_mintable: bool,
 	pub is_burnable: bool,
     pub description: Option<StdString>,
+	/// total issuance never goes above it once set
+	pub max_supply: Option<Balance>,
 }
 
 impl Default for AssetInfo {
@@ -42,6 +46,7 @@ impl Default for AssetInfo {
 			is_mintable: false,
 			is_burnable: false,
 			description: None,
+			max_supply: None,
         }
     }
 }
@@ -55,27 +60,19 @@ pub struct SwapVolume {
 
 #[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Hash))]
-pub struct PoolInfo<AccountId: Ord> {
+pub struct PoolInfo {
 	pub pid: PoolId,
 	pub total_fee: u32,
 	pub exchange_fee: u32,
-	pub shares_total_supply: Balance,
-	pub shares_data: SharesData<AccountId>,
-	pub symbol_data: SymbolData,
-	pub volume_data: VolumeData,
 	pub description: Option<StdString>,
 }
 
-impl<AccountId: Ord> Default for PoolInfo<AccountId> {
+impl Default for PoolInfo {
     fn default() -> Self {
         PoolInfo {
             pid: DEFAULT_POOL_ID,
 			total_fee: 0,
 			exchange_fee: 0,
-			shares_total_supply: 0,
-			shares_data: Default::default(),
-			symbol_data: Default::default(),
-			volume_data: Default::default(),
 			description: None,
         }
     }
@@ -197,6 +197,9 @@ impl Default for FixedString {
 pub enum PoolType{
 	None,
 	AmmPool,
+	StablePool,
+	WeightedPool,
+	OrderBook,
 }
 
 impl Default for PoolType{
@@ -148,6 +148,11 @@ fn testnet_genesis(
 	nft_tokens.insert(NftTokenId::from_string("endowed_token1"));
 	nft_tokens.insert(NftTokenId::from_string("endowed_token2"));
 
+	// the first endowed account provides the initial liquidity, DOT is registered with it by the pool genesis
+	let mut root_pool_symbols = BTreeMap::new();
+	root_pool_symbols.insert(DEFAULT_SYMBOL.clone(), 100000000000000000u128);
+	root_pool_symbols.insert(AssetSymbol::from_string("DOT"), 100000000000000000u128);
+
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
 			// Add Wasm runtime to storage.
@@ -187,6 +192,7 @@ fn testnet_genesis(
 					true,
 					true,
 					None,
+					None,
                     0,
                 ),
 			],
@@ -195,16 +201,17 @@ fn testnet_genesis(
             endowed_pool: vec![
                 (
                     endowed_accounts[0].to_owned(),
-					DEFAULT_SYMBOL.clone(),
+					DEFAULT_POOL_ID.clone(),
 					0,
 					0,
-					BTreeMap::new(),
+					root_pool_symbols,
 					None,
                 ),
 			],
         }),
 		pallet_pool_manager: Some(PoolManagerConfig {
 			endowed_pool: vec![
+				(DEFAULT_POOL_ID.clone(), PoolType::AmmPool),
 			],
         }),
 		pallet_nft: Some(NFTConfig {
@@ -225,7 +232,7 @@ fn testnet_genesis(
 					FarmId::from_string("endowed_farm1"),
 					FixedString::from_string("DOT"),
 					None,
-					Some((NftClassId::from_string("Class1"), NftClassId::from_string("NFT1"))),
+					Some((NftClassId::from_string("endowed_class1"), NftTokenId::from_string("endowed_token1"))),
 					10,
 					2,
                 ),
@@ -233,3 +240,30 @@ fn testnet_genesis(
         }),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::BuildStorage;
+	use node_polkaexchange_runtime::{PoolAmm, PoolManager};
+
+	fn assert_endowed_pools(chain_spec: ChainSpec) {
+		let storage = chain_spec.build_storage().expect("Failed to build genesis storage.");
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			assert!(PoolAmm::get_pool_owner(&DEFAULT_POOL_ID).is_some());
+			assert_eq!(PoolManager::pool_instances(&DEFAULT_POOL_ID), PoolType::AmmPool);
+			assert!(PoolManager::is_swappable_in_pool(&DEFAULT_POOL_ID, &DEFAULT_SYMBOL, &AssetSymbol::from_string("DOT")));
+			assert!(PoolAmm::ensure_reserves_consistent(&DEFAULT_POOL_ID).is_ok());
+		});
+	}
+
+	#[test]
+	fn test_development_config() {
+		assert_endowed_pools(development_config().expect("Failed to create the development config."));
+	}
+
+	#[test]
+	fn test_local_testnet_config() {
+		assert_endowed_pools(local_testnet_config().expect("Failed to create the local testnet config."));
+	}
+}
@@ -0,0 +1,378 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{ensure, transactional, RuntimeDebug};
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use frame_system::ensure_signed;
+use sp_runtime::traits::{One, Zero};
+use sp_std::vec::Vec;
+use sp_std::cmp::min;
+use traits::MultiReservableCurrency;
+
+pub use base::*;
+
+type FungibleAsset<T> = pallet_fungible_asset::Pallet<T>;
+type PoolManager<T> = pallet_pool_manager::Pallet<T>;
+type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
+
+pub type ScheduleId = u64;
+
+/// blocks looked at for room to run an instalment in, past them it is carried over from block to block
+pub const MAX_SCHEDULING_DELAY: u32 = 32;
+
+/// Swaps `amount_per_instalment` of `asset_in` along `path` every `period` blocks until the budget is spent
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct Schedule<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	pub asset_in: AssetSymbol,
+	/// hops the instalment is swapped through, each a pool and the asset it swaps into
+	pub path: SwapPath,
+	pub amount_per_instalment: Balance,
+	/// least output accepted from a full instalment, scaled down for the last smaller one
+	pub min_amount_out: Balance,
+	pub period: BlockNumber,
+	/// input still reserved for the coming instalments
+	pub remaining_budget: Balance,
+	pub next_execution: BlockNumber,
+	/// instalments failed in a row
+	pub failed_instalments: u32,
+	/// native asset reserved until the schedule ends
+	pub deposit: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+    pub trait Config:
+        frame_system::Config + pallet_pool_manager::Config
+    {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Instalments run in one block, the others are pushed to the following blocks
+		#[pallet::constant]
+		type MaxSchedulesPerBlock: Get<u32>;
+
+		/// A schedule is stopped and its budget given back once this many instalments failed in a row
+		#[pallet::constant]
+		type MaxFailedInstalments: Get<u32>;
+
+		/// Native asset reserved for each schedule, given back when it completes, is cancelled or stopped
+		#[pallet::constant]
+		type ScheduleDeposit: Get<Balance>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			let mut schedule_ids = ScheduledAt::<T>::take(now);
+			let max_schedules = T::MaxSchedulesPerBlock::get() as usize;
+			if schedule_ids.len() > max_schedules {
+				let carried = schedule_ids.split_off(max_schedules);
+				weight = weight.saturating_add(Self::carry_over(carried, now + One::one()));
+			}
+			for schedule_id in schedule_ids {
+				weight = weight.saturating_add(Self::execute_instalment(schedule_id, now));
+			}
+			weight
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(4, 4) * path.len() as u64)]
+		pub fn create_schedule(
+			origin: OriginFor<T>,
+			asset_in: AssetSymbol,
+			path: SwapPath,
+			amount_per_instalment: Balance,
+			min_amount_out: Balance,
+			period: T::BlockNumber,
+			budget: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::create_dca_schedule(
+				&who,
+				&asset_in,
+				&path,
+				amount_per_instalment,
+				min_amount_out,
+				period,
+				budget,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn cancel_schedule(
+			origin: OriginFor<T>,
+			schedule_id: ScheduleId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::cancel_dca_schedule(
+				&who,
+				schedule_id,
+			)?;
+			Ok(().into())
+		}
+	}
+
+	#[pallet::storage]
+    #[pallet::getter(fn schedules)]
+    pub type Schedules<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        ScheduleId,
+		Schedule<T::AccountId, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+	/// schedules with an instalment due in the block
+	#[pallet::storage]
+    #[pallet::getter(fn scheduled_at)]
+    pub type ScheduledAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::BlockNumber,
+		Vec<ScheduleId>,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn next_schedule_id)]
+    pub type NextScheduleId<T: Config> = StorageValue<_, ScheduleId, ValueQuery>;
+
+	#[pallet::error]
+    pub enum Error<T> {
+		ZeroAmount,
+		ZeroPeriod,
+		ScheduleNotExists,
+		NotScheduleOwner,
+	}
+
+	#[pallet::event]
+    #[pallet::metadata(AccountIdOf<T> = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+		/// schedule, owner and the reserved budget
+		ScheduleCreated(ScheduleId, AccountIdOf<T>, Balance),
+		/// schedule, owner, amount in and amount out
+		InstalmentExecuted(ScheduleId, AccountIdOf<T>, Balance, Balance),
+		/// the input stays reserved and the instalment is tried again after a period
+		InstalmentFailed(ScheduleId, AccountIdOf<T>, DispatchError),
+		ScheduleCompleted(ScheduleId, AccountIdOf<T>),
+		/// schedule, owner and the budget given back
+		ScheduleCancelled(ScheduleId, AccountIdOf<T>, Balance),
+		/// too many failed instalments in a row, the budget is given back
+		ScheduleStopped(ScheduleId, AccountIdOf<T>, Balance),
+	}
+}
+
+pub use pallet::*;
+
+impl<T: Config> Pallet<T> {
+	/// Reserves `budget` and the schedule deposit, the first instalment runs in the next block with room left
+	#[transactional]
+	pub fn create_dca_schedule(
+		who: &T::AccountId,
+		asset_in: &AssetSymbol,
+		path: &SwapPath,
+		amount_per_instalment: Balance,
+		min_amount_out: Balance,
+		period: T::BlockNumber,
+		budget: Balance,
+	) -> DispatchResult {
+		ensure!(
+			amount_per_instalment > 0 && budget > 0,
+			Error::<T>::ZeroAmount
+		);
+		ensure!(
+			!period.is_zero(),
+			Error::<T>::ZeroPeriod
+		);
+		PoolManager::<T>::get_swap_return_asset_with_path(asset_in, amount_per_instalment, path)?;
+		ensure!(
+			path.last().map_or(false, |(_, asset_out)| asset_out != asset_in),
+			pallet_pool_manager::Error::<T>::InvalidSwapPath
+		);
+
+		FungibleAsset::<T>::ensure_not_frozen(asset_in, who)?;
+		T::Currency::reserve(FungibleAsset::<T>::get_asset_id(asset_in), who, budget)?;
+		let deposit = T::ScheduleDeposit::get();
+		if deposit > 0 {
+			T::Currency::reserve(T::NativeAssetId::get(), who, deposit)?;
+		}
+		let schedule_id = NextScheduleId::<T>::get();
+		NextScheduleId::<T>::put(schedule_id + 1);
+		let next_execution = Self::plan_instalment(schedule_id, <frame_system::Pallet<T>>::block_number() + One::one());
+		Schedules::<T>::insert(schedule_id, Schedule {
+			owner: who.clone(),
+			asset_in: asset_in.clone(),
+			path: path.clone(),
+			amount_per_instalment,
+			min_amount_out,
+			period,
+			remaining_budget: budget,
+			next_execution,
+			failed_instalments: 0,
+			deposit,
+		});
+		Self::deposit_event(Event::ScheduleCreated(schedule_id, who.clone(), budget));
+		Ok(())
+	}
+
+	/// Stops the schedule and gives back what is left of the budget
+	pub fn cancel_dca_schedule(
+		who: &T::AccountId,
+		schedule_id: ScheduleId,
+	) -> DispatchResult {
+		let schedule = Schedules::<T>::get(schedule_id).ok_or(Error::<T>::ScheduleNotExists)?;
+		ensure!(
+			schedule.owner == *who,
+			Error::<T>::NotScheduleOwner
+		);
+
+		T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(&schedule.asset_in), who, schedule.remaining_budget);
+		ScheduledAt::<T>::mutate(schedule.next_execution, |schedule_ids| schedule_ids.retain(|id| *id != schedule_id));
+		Self::remove_schedule(schedule_id, &schedule);
+		Self::deposit_event(Event::ScheduleCancelled(schedule_id, who.clone(), schedule.remaining_budget));
+		Ok(())
+	}
+
+	/// Puts the schedule in the first block from `block` with room left and returns that block. When the next
+	/// `MAX_SCHEDULING_DELAY` blocks are all full it goes in the last of them and is carried over from there.
+	fn plan_instalment(schedule_id: ScheduleId, block: T::BlockNumber) -> T::BlockNumber {
+		let mut block = block;
+		for _ in 1..MAX_SCHEDULING_DELAY {
+			if ScheduledAt::<T>::decode_len(block).unwrap_or(0) < T::MaxSchedulesPerBlock::get() as usize {
+				break;
+			}
+			block += One::one();
+		}
+		ScheduledAt::<T>::append(block, schedule_id);
+		block
+	}
+
+	/// Moves the instalments a block has no room for to the front of `block`, returns the weight it took
+	fn carry_over(schedule_ids: Vec<ScheduleId>, block: T::BlockNumber) -> Weight {
+		for schedule_id in &schedule_ids {
+			Schedules::<T>::mutate(schedule_id, |schedule| {
+				if let Some(schedule) = schedule {
+					schedule.next_execution = block;
+				}
+			});
+		}
+		let carried = schedule_ids.len() as Weight;
+		ScheduledAt::<T>::mutate(block, |planned| {
+			let mut schedule_ids = schedule_ids;
+			schedule_ids.append(planned);
+			*planned = schedule_ids;
+		});
+		T::DbWeight::get().reads_writes(carried + 1, carried + 1)
+	}
+
+	/// Runs the due instalment and plans the next one, returns the weight it took
+	fn execute_instalment(schedule_id: ScheduleId, now: T::BlockNumber) -> Weight {
+		let mut schedule = match Schedules::<T>::get(schedule_id) {
+			Some(schedule) => schedule,
+			None => return T::DbWeight::get().reads(1),
+		};
+		// every hop of the path is a swap of its own
+		let weight = T::DbWeight::get().reads_writes(4, 4)
+			+ (2_000_000 + T::DbWeight::get().reads_writes(4, 4)) * schedule.path.len() as Weight;
+
+		let amount_in = min(schedule.amount_per_instalment, schedule.remaining_budget);
+		match Self::swap_instalment(&schedule, amount_in) {
+			Ok(amount_out) => {
+				schedule.remaining_budget -= amount_in;
+				schedule.failed_instalments = 0;
+				Self::deposit_event(Event::InstalmentExecuted(schedule_id, schedule.owner.clone(), amount_in, amount_out));
+			}
+			Err(error) => {
+				schedule.failed_instalments += 1;
+				Self::deposit_event(Event::InstalmentFailed(schedule_id, schedule.owner.clone(), error));
+			}
+		}
+
+		if schedule.remaining_budget == 0 {
+			Self::remove_schedule(schedule_id, &schedule);
+			Self::deposit_event(Event::ScheduleCompleted(schedule_id, schedule.owner));
+			return weight;
+		}
+		if schedule.failed_instalments >= T::MaxFailedInstalments::get() {
+			Self::stop_schedule(schedule_id, &schedule);
+			return weight;
+		}
+		schedule.next_execution = Self::plan_instalment(schedule_id, now + schedule.period);
+		Schedules::<T>::insert(schedule_id, schedule);
+		weight
+	}
+
+	fn stop_schedule(
+		schedule_id: ScheduleId,
+		schedule: &Schedule<T::AccountId, T::BlockNumber>,
+	) {
+		T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(&schedule.asset_in), &schedule.owner, schedule.remaining_budget);
+		Self::remove_schedule(schedule_id, schedule);
+		Self::deposit_event(Event::ScheduleStopped(schedule_id, schedule.owner.clone(), schedule.remaining_budget));
+	}
+
+	/// Gives the deposit back and removes the schedule, the budget is left to the caller
+	fn remove_schedule(
+		schedule_id: ScheduleId,
+		schedule: &Schedule<T::AccountId, T::BlockNumber>,
+	) {
+		if schedule.deposit > 0 {
+			T::Currency::unreserve(T::NativeAssetId::get(), &schedule.owner, schedule.deposit);
+		}
+		Schedules::<T>::remove(schedule_id);
+	}
+
+	/// Swaps `amount_in` of the reserved budget through `pallet_pool_manager`, nothing changes when it fails
+	#[transactional]
+	fn swap_instalment(
+		schedule: &Schedule<T::AccountId, T::BlockNumber>,
+		amount_in: Balance,
+	) -> Result<Balance, DispatchError> {
+		let min_amount_out = if amount_in == schedule.amount_per_instalment {
+			schedule.min_amount_out
+		}
+		else {
+			schedule.min_amount_out.saturating_mul(amount_in) / schedule.amount_per_instalment
+		};
+		let asset_out = schedule.path.last().map(|(_, asset_out)| asset_out.clone()).ok_or(pallet_pool_manager::Error::<T>::EmptySwapPath)?;
+		let out_before = FungibleAsset::<T>::free_balance(&asset_out, &schedule.owner)?;
+
+		T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(&schedule.asset_in), &schedule.owner, amount_in);
+		match schedule.path.as_slice() {
+			[(pid, asset_out)] => {
+				PoolManager::<T>::swap_asset_in_pool(&schedule.owner, pid, &schedule.asset_in, amount_in, asset_out, min_amount_out)?
+			}
+			_ => PoolManager::<T>::swap_with_path(&schedule.owner, &schedule.asset_in, amount_in, &schedule.path, min_amount_out, None)?,
+		}
+
+		let out_after = FungibleAsset::<T>::free_balance(&asset_out, &schedule.owner)?;
+		Ok(out_after.saturating_sub(out_before))
+	}
+}
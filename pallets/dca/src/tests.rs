@@ -0,0 +1,117 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+mod tests {
+    use crate::mock::*;
+    use crate::Error;
+    use frame_support::{assert_noop, assert_ok};
+	use frame_support::traits::OnInitialize;
+	use sp_std::collections::btree_map::BTreeMap;
+
+	use base::*;
+
+	fn create_pool(pid: &PoolId, symbol1: &AssetSymbol, symbol2: &AssetSymbol) {
+		let mut symbol_data: SymbolData = BTreeMap::new();
+		symbol_data.insert(symbol1.clone(), 10_000);
+		symbol_data.insert(symbol2.clone(), 10_000);
+		assert_ok!(PoolManager::create_amm_pool(&ALICE, pid, 30, 10, &symbol_data, None));
+	}
+
+	#[test]
+	fn test_schedule() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool(&POOL1, &TEST_SYMBOL1, &TEST_SYMBOL2);
+			create_pool(&POOL2, &TEST_SYMBOL2, &TEST_SYMBOL3);
+			let single_hop: SwapPath = vec![(POOL1, TEST_SYMBOL2)];
+			let two_hops: SwapPath = vec![(POOL1, TEST_SYMBOL2), (POOL2, TEST_SYMBOL3)];
+
+			assert_noop!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &single_hop, 100, 1, 0, 250),
+				Error::<Runtime>::ZeroPeriod);
+			assert_noop!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &single_hop, 0, 1, 10, 250),
+				Error::<Runtime>::ZeroAmount);
+			assert_noop!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &vec![(POOL1, TEST_SYMBOL2), (POOL1, TEST_SYMBOL1)], 100, 1, 10, 250),
+				pallet_pool_manager::Error::<Runtime>::InvalidSwapPath);
+
+			assert_ok!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &single_hop, 100, 1, 10, 250));
+			assert_ok!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &two_hops, 100, 1_000_000, 5, 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 350));
+			// one instalment a block, the second schedule is pushed to the next block
+			assert_eq!(Dca::scheduled_at(1), vec![0]);
+			assert_eq!(Dca::scheduled_at(2), vec![1]);
+
+			let amount_out = PoolManager::get_swap_return_asset_from_pool(&POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2);
+			Dca::on_initialize(1);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + amount_out));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(Dca::schedules(0).map(|schedule| (schedule.remaining_budget, schedule.next_execution)), Some((150, 11)));
+
+			// the minimum output is not met, the budget stays reserved for the next period
+			Dca::on_initialize(2);
+			assert_eq!(Dca::schedules(1).map(|schedule| (schedule.remaining_budget, schedule.next_execution)), Some((100, 7)));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL3, &BOB), Ok(INITIAL_BALANCE));
+
+			Dca::on_initialize(11);
+			Dca::on_initialize(21);
+			assert_eq!(Dca::schedules(0), None);
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 250));
+
+			assert_noop!(Dca::cancel_dca_schedule(&ALICE, 1), Error::<Runtime>::NotScheduleOwner);
+			assert_ok!(Dca::cancel_dca_schedule(&BOB, 1));
+			assert_noop!(Dca::cancel_dca_schedule(&BOB, 1), Error::<Runtime>::ScheduleNotExists);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 250));
+			assert_eq!(Dca::scheduled_at(7), Vec::<u64>::new());
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL1));
+		});
+	}
+
+	#[test]
+	fn test_stopped_schedule() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool(&POOL1, &TEST_SYMBOL1, &TEST_SYMBOL2);
+			let single_hop: SwapPath = vec![(POOL1, TEST_SYMBOL2)];
+
+			// a minimum output the pool never pays, given up after the second failure in a row
+			assert_ok!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &single_hop, 100, 1_000_000, 5, 300));
+			assert_eq!(Balances::reserved_balance(&BOB), 10);
+			Dca::on_initialize(1);
+			assert_eq!(Dca::schedules(0).map(|schedule| (schedule.failed_instalments, schedule.next_execution)), Some((1, 6)));
+			Dca::on_initialize(6);
+			assert_eq!(Dca::schedules(0), None);
+			assert_eq!(Dca::scheduled_at(11), Vec::<u64>::new());
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE));
+			assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+		});
+	}
+
+	#[test]
+	fn test_carried_over_schedule() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool(&POOL1, &TEST_SYMBOL1, &TEST_SYMBOL2);
+			let single_hop: SwapPath = vec![(POOL1, TEST_SYMBOL2)];
+
+			// only the next blocks are searched for room, the last of them takes the rest
+			for _ in 0..crate::MAX_SCHEDULING_DELAY + 1 {
+				assert_ok!(Dca::create_dca_schedule(&BOB, &TEST_SYMBOL1, &single_hop, 100, 1, 5, 200));
+			}
+			let last_block = crate::MAX_SCHEDULING_DELAY as u64;
+			assert_eq!(Dca::scheduled_at(last_block), vec![31, 32]);
+			assert_eq!(Balances::reserved_balance(&BOB), 10 * (last_block + 1) as Balance);
+
+			// what a block has no room for runs in the next one instead of being stopped
+			Dca::on_initialize(last_block);
+			assert_eq!(Dca::schedules(31).map(|schedule| schedule.next_execution), Some(last_block + 5));
+			assert_eq!(Dca::scheduled_at(last_block + 1), vec![32]);
+			assert_eq!(Dca::schedules(32).map(|schedule| (schedule.remaining_budget, schedule.next_execution)), Some((200, last_block + 1)));
+			Dca::on_initialize(last_block + 1);
+			assert_eq!(Dca::schedules(32).map(|schedule| (schedule.remaining_budget, schedule.next_execution)), Some((100, last_block + 6)));
+
+			assert_ok!(Dca::cancel_dca_schedule(&BOB, 32));
+			assert_eq!(Dca::scheduled_at(last_block + 6), Vec::<u64>::new());
+		});
+	}
+}
@@ -62,6 +62,7 @@ parameter_types! {
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(80);
 
 	pub const GetBaseAssetId: AssetId = DEFAULT_SYMBOL;
+	pub const AssetDeposit: Balance = 0;
 	pub const ExistentialDeposit: u128 = 0;
 }
 
@@ -128,6 +129,8 @@ impl pallet_fungible_asset::Config for Runtime {
     type Event = Event;
     type AssetId = AssetId;
     type Currency = currencies::Module<Runtime>;
+    type NativeAssetId = GetBaseAssetId;
+    type AssetDeposit = AssetDeposit;
 }
 
 impl pallet_nft::Config for Runtime {
@@ -11,8 +11,8 @@ use mock::*;
 use crate::Error;
 
 fn init_test_env(shares_init_supply: Balance, stake_init_supply: Balance) {
-    assert_ok!(PalletFungibleAsset::register_asset(&ALICE, &TEST_SHARES_FT_TOKEN1, &TEST_SHARES_FT_TOKEN1, 8, true, true, None, shares_init_supply));
-    assert_ok!(PalletFungibleAsset::register_asset(&ALICE, &TEST_STAKE_TOKEN1, &TEST_STAKE_TOKEN1, 8, true, true, None, stake_init_supply));
+    assert_ok!(PalletFungibleAsset::register_asset(&ALICE, &TEST_SHARES_FT_TOKEN1, &TEST_SHARES_FT_TOKEN1, 8, true, true, None, None, shares_init_supply));
+    assert_ok!(PalletFungibleAsset::register_asset(&ALICE, &TEST_STAKE_TOKEN1, &TEST_STAKE_TOKEN1, 8, true, true, None, None, stake_init_supply));
     // 2 nfts mint by ALICE
     assert_ok!(PalletNFT::create_nft_class(&ALICE, &TEST_STAKE_NFT_CLASS1, &()));
     assert_ok!(PalletNFT::mint_token(&ALICE, &TEST_STAKE_NFT_CLASS1, &TEST_STAKE_NFT_TOKEN1, &TokenMetadata::default(), &()));
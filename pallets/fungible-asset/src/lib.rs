@@ -18,8 +18,11 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod migration;
+
+use codec::{Decode, Encode};
 use frame_support::sp_runtime::traits::{MaybeSerializeDeserialize, Member};
-use frame_support::{ensure, Parameter};
+use frame_support::{ensure, transactional, Parameter, RuntimeDebug};
 use frame_support::dispatch::{DispatchError, DispatchResult};
 use frame_system::{ensure_root, ensure_signed};
 use sp_std::vec::Vec;
@@ -35,6 +38,21 @@ pub type AssetIdOf<T> = <T as Config>::AssetId;
 pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
 type CurrencyIdOf<T> = <<T as Config>::Currency as MultiCurrency<<T as frame_system::Config>::AccountId>>::CurrencyId;
 
+/// Storage layout versions of the pallet
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum Releases {
+	/// `AssetInfo` without `max_supply`
+	V1,
+	/// `AssetInfo` ends with `max_supply`
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
 pub use pallet::*;
 
 #[frame_support::pallet]
@@ -67,6 +85,13 @@ pub mod pallet {
                 Balance = Balance,
             > + MultiReservableCurrency<Self::AccountId, CurrencyId = Self::AssetId, Balance = Balance>
             + MultiCurrencyExtended<Self::AccountId, Amount = Amount>;
+
+        /// The native asset the registration deposit is reserved in
+        type NativeAssetId: Get<Self::AssetId>;
+
+        /// Reserved from the owner of an asset registered by a signed origin, given back when it is destroyed
+        #[pallet::constant]
+        type AssetDeposit: Get<Balance>;
     }
 
     #[pallet::pallet]
@@ -74,7 +99,19 @@ pub mod pallet {
     pub struct Pallet<T>(PhantomData<T>);
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::<T>::get() == Releases::V1 {
+				migration::migrate_to_v2::<T>()
+			}
+			else {
+				0
+			}
+		}
+	}
+
+	#[pallet::storage]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
 	#[pallet::storage]
     #[pallet::getter(fn asset_permission)]
@@ -106,6 +143,91 @@ pub mod pallet {
         ValueQuery,
     >;
 
+	/// amount of the asset the spender can still transfer from the owner, keyed by (owner, spender)
+	#[pallet::storage]
+    #[pallet::getter(fn allowances)]
+    pub type Allowances<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+        Twox64Concat,
+        (T::AccountId, T::AccountId),
+		Balance,
+        ValueQuery,
+    >;
+
+	/// owner proposed by the current owner of the asset, who becomes the owner once accepting
+	#[pallet::storage]
+    #[pallet::getter(fn pending_asset_owners)]
+    pub type PendingAssetOwners<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+		T::AccountId,
+        OptionQuery,
+    >;
+
+	/// accounts the owner of the asset blocked from moving it
+	#[pallet::storage]
+    #[pallet::getter(fn frozen_accounts)]
+    pub type FrozenAccounts<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+        Twox64Concat,
+		T::AccountId,
+		bool,
+        ValueQuery,
+    >;
+
+	/// assets nobody can move but their owner through `force_transfer`
+	#[pallet::storage]
+    #[pallet::getter(fn frozen_assets)]
+    pub type FrozenAssets<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+		bool,
+        ValueQuery,
+    >;
+
+	/// accounts the owner allowed to mint the asset
+	#[pallet::storage]
+    #[pallet::getter(fn asset_minters)]
+    pub type AssetMinters<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+        Twox64Concat,
+		T::AccountId,
+		bool,
+        ValueQuery,
+    >;
+
+	/// accounts the owner allowed to burn the asset
+	#[pallet::storage]
+    #[pallet::getter(fn asset_burners)]
+    pub type AssetBurners<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+        Twox64Concat,
+		T::AccountId,
+		bool,
+        ValueQuery,
+    >;
+
+	/// native deposit reserved from the owner of each asset
+	#[pallet::storage]
+    #[pallet::getter(fn asset_deposits)]
+    pub type AssetDeposits<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AssetId,
+		Balance,
+        ValueQuery,
+    >;
+
     #[pallet::call]
     impl<T: Config> Pallet<T> {
 		#[pallet::weight(1_000_000 + T::DbWeight::get().writes(1))]
@@ -134,11 +256,11 @@ pub mod pallet {
 			is_mintable: bool,
 			is_burnable: bool,
 			description: Option<StdString>,
+			max_supply: Option<Balance>,
 			initial_supply: Balance,
         ) -> DispatchResultWithPostInfo {
-			ensure_root(origin.clone())?;
 			let issuer = ensure_signed(origin)?;
-			Self::register_asset(
+			Self::register_asset_with_deposit(
 				&issuer,
 				&symbol,
 				&name,
@@ -146,11 +268,53 @@ pub mod pallet {
 				is_mintable,
 				is_burnable,
 				description,
+				max_supply,
+				initial_supply,
+			)?;
+            Ok(().into())
+        }
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 2))]
+        pub fn force_register(
+            origin: OriginFor<T>,
+            owner: T::AccountId,
+            symbol: AssetSymbol,
+			name: AssetName,
+			precision: BalancePrecision,
+			is_mintable: bool,
+			is_burnable: bool,
+			description: Option<StdString>,
+			max_supply: Option<Balance>,
+			initial_supply: Balance,
+        ) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::force_register_asset(
+				&owner,
+				&symbol,
+				&name,
+				precision,
+				is_mintable,
+				is_burnable,
+				description,
+				max_supply,
 				initial_supply,
 			)?;
             Ok(().into())
         }
 
+		#[pallet::weight(5_000_000 + T::DbWeight::get().reads_writes(3, 4))]
+        pub fn destroy(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::destroy_asset(
+				&issuer,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
+
 		#[pallet::weight(5_000_000 + T::DbWeight::get().writes(1))]
         pub fn mint(
             origin: OriginFor<T>,
@@ -197,99 +361,426 @@ pub mod pallet {
 			)?;
 			Ok(().into())
 		}
-	}
 
-    #[pallet::event]
-    #[pallet::metadata(AccountIdOf<T> = "AccountId", AssetIdOf<T> = "AssetId")]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-		AssetRegistered(AssetIdOf<T>, AccountIdOf<T>),
-		Mint(AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
-		Burn(AccountIdOf<T>, AssetIdOf<T>, Balance),
-		Transfer(AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
-    }
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn update_name(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			name: AssetName,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::update_asset_name(
+				&issuer,
+				&symbol,
+				&name,
+			)?;
+			Ok(().into())
+		}
 
-    #[pallet::error]
-    pub enum Error<T> {
-		AssetNotAllowedToRegister,
-		AssetAlreadyExists,
-		InvalidAssetSymbol,
-		InvalidAssetName,
-		InvalidPrecision,
-		InvalidOwner,
-		AssetNotExists,
-		AssetIsNotMintable,
-		AssetIsNotBurnable,
-		NoEnoughBalance,
-		IncRefError,
-    }
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn update_description(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			description: Option<StdString>,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::update_asset_description(
+				&issuer,
+				&symbol,
+				description,
+			)?;
+			Ok(().into())
+		}
 
-    #[pallet::genesis_config]
-    pub struct GenesisConfig<T: Config> {
-        pub endowed_assets: Vec<(
-			T::AccountId,
-            AssetSymbol,
-            AssetName,
-            BalancePrecision,
-			bool,
-			bool,
-			Option<StdString>,
-            Balance,
-        )>,
-    }
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn renounce_minting(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::renounce_asset_minting(
+				&issuer,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
 
-    #[cfg(feature = "std")]
-    impl<T: Config> Default for GenesisConfig<T> {
-        fn default() -> Self {
-            Self {
-                endowed_assets: Default::default(),
-            }
-        }
-    }
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn renounce_burning(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::renounce_asset_burning(
+				&issuer,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
 
-    #[pallet::genesis_build]
-    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
-        fn build(&self) {
-			self.endowed_assets.iter().cloned().for_each(
-                |(account_id, symbol, name, precision, is_mintable, is_burnable, description, initial_supply)| {
-                    Pallet::<T>::register_asset(
-                        &account_id,
-                        &symbol,
-                        &name,
-                        precision,
-						is_mintable,
-						is_burnable,
-						description,
-                        initial_supply,
-                    )
-                    .expect("Failed to register asset.");
-				},
-			)
-        }
-    }
-}
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn propose_owner(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			new_owner: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::propose_asset_owner(
+				&issuer,
+				&symbol,
+				&new_owner,
+			)?;
+			Ok(().into())
+		}
 
-impl<T: Config> Pallet<T> {
-	#[inline]
-	pub fn get_asset_id(symbol: &AssetSymbol) -> T::AssetId {
-		T::AssetId::from(symbol.0)
-	}
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(3, 4))]
+        pub fn accept_ownership(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::accept_asset_ownership(
+				&who,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
 
-	#[inline]
-	pub fn get_asset_symbol(asset_id: &T::AssetId) -> AssetSymbol {
-		FixedString(asset_id.to_fixed_array())
-	}
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 1))]
+        pub fn freeze(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::freeze_account(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
 
-	pub fn get_asset_owner(asset_id: &T::AssetId) -> Option<T::AccountId> {
-        let account_id = Self::asset_owner_list(&asset_id);
-        if account_id == T::AccountId::default() {
-            None
-        } else {
-            Some(account_id)
-        }
-    }
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 1))]
+        pub fn thaw(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::thaw_account(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
 
-	pub fn ensure_asset_exists(asset_id: &T::AssetId) -> DispatchResult {
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 1))]
+        pub fn freeze_asset(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::freeze_asset_transfers(
+				&issuer,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 1))]
+        pub fn thaw_asset(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::thaw_asset_transfers(
+				&issuer,
+				&symbol,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 2))]
+        pub fn force_transfer(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			from: T::AccountId,
+			to: T::AccountId,
+			amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::force_transfer_asset(
+				&issuer,
+				&symbol,
+				&from,
+				&to,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn add_minter(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::add_asset_minter(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn remove_minter(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::remove_asset_minter(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn add_burner(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::add_asset_burner(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn remove_burner(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			who: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::remove_asset_burner(
+				&issuer,
+				&symbol,
+				&who,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn approve(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			spender: T::AccountId,
+			amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			Self::approve_asset(
+				&owner,
+				&symbol,
+				&spender,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn increase_allowance(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			spender: T::AccountId,
+			amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			Self::increase_asset_allowance(
+				&owner,
+				&symbol,
+				&spender,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn decrease_allowance(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			spender: T::AccountId,
+			amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+			Self::decrease_asset_allowance(
+				&owner,
+				&symbol,
+				&spender,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(3_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn transfer_from(
+            origin: OriginFor<T>,
+            symbol: AssetSymbol,
+			owner: T::AccountId,
+			to: T::AccountId,
+			amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+			let spender = ensure_signed(origin)?;
+			Self::transfer_asset_from(
+				&spender,
+				&symbol,
+				&owner,
+				&to,
+				amount,
+			)?;
+			Ok(().into())
+		}
+	}
+
+    #[pallet::event]
+    #[pallet::metadata(AccountIdOf<T> = "AccountId", AssetIdOf<T> = "AssetId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+		AssetRegistered(AssetIdOf<T>, AccountIdOf<T>),
+		/// asset, owner and the deposit given back
+		AssetDestroyed(AssetIdOf<T>, AccountIdOf<T>, Balance),
+		Mint(AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
+		Burn(AccountIdOf<T>, AssetIdOf<T>, Balance),
+		Transfer(AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
+		/// owner, spender, asset and the allowance left
+		Approval(AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
+		AssetNameUpdated(AssetIdOf<T>, AssetName, AssetName),
+		AssetDescriptionUpdated(AssetIdOf<T>, Option<StdString>, Option<StdString>),
+		MintingRenounced(AssetIdOf<T>, AccountIdOf<T>),
+		BurningRenounced(AssetIdOf<T>, AccountIdOf<T>),
+		/// asset, current and proposed owner
+		AssetOwnerProposed(AssetIdOf<T>, AccountIdOf<T>, AccountIdOf<T>),
+		AssetOwnerChanged(AssetIdOf<T>, AccountIdOf<T>, AccountIdOf<T>),
+		AccountFrozen(AssetIdOf<T>, AccountIdOf<T>),
+		AccountThawed(AssetIdOf<T>, AccountIdOf<T>),
+		AssetFrozen(AssetIdOf<T>),
+		AssetThawed(AssetIdOf<T>),
+		/// issuer, from, to, asset and amount
+		ForceTransfer(AccountIdOf<T>, AccountIdOf<T>, AccountIdOf<T>, AssetIdOf<T>, Balance),
+		MinterAdded(AssetIdOf<T>, AccountIdOf<T>),
+		MinterRemoved(AssetIdOf<T>, AccountIdOf<T>),
+		BurnerAdded(AssetIdOf<T>, AccountIdOf<T>),
+		BurnerRemoved(AssetIdOf<T>, AccountIdOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+		AssetNotAllowedToRegister,
+		AssetAlreadyExists,
+		InvalidAssetSymbol,
+		InvalidAssetName,
+		InvalidPrecision,
+		InvalidOwner,
+		AssetNotExists,
+		AssetIsNotMintable,
+		AssetIsNotBurnable,
+		NoEnoughBalance,
+		IncRefError,
+		DecRefError,
+		AssetHasSupply,
+		NoEnoughAllowance,
+		NotPendingOwner,
+		AccountIsFrozen,
+		AccountIsNotFrozen,
+		AssetIsFrozen,
+		AssetIsNotFrozen,
+		ExceedsMaxSupply,
+		AlreadyMinter,
+		NotMinter,
+		AlreadyBurner,
+		NotBurner,
+		ReservedAssetSymbol,
+    }
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        pub endowed_assets: Vec<(
+			T::AccountId,
+            AssetSymbol,
+            AssetName,
+            BalancePrecision,
+			bool,
+			bool,
+			Option<StdString>,
+			Option<Balance>,
+            Balance,
+        )>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self {
+                endowed_assets: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+			StorageVersion::<T>::put(Releases::V2);
+			self.endowed_assets.iter().cloned().for_each(
+                |(account_id, symbol, name, precision, is_mintable, is_burnable, description, max_supply, initial_supply)| {
+                    Pallet::<T>::register_asset(
+                        &account_id,
+                        &symbol,
+                        &name,
+                        precision,
+						is_mintable,
+						is_burnable,
+						description,
+						max_supply,
+                        initial_supply,
+                    )
+                    .expect("Failed to register asset.");
+				},
+			)
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+	#[inline]
+	pub fn get_asset_id(symbol: &AssetSymbol) -> T::AssetId {
+		T::AssetId::from(symbol.0)
+	}
+
+	#[inline]
+	pub fn get_asset_symbol(asset_id: &T::AssetId) -> AssetSymbol {
+		FixedString(asset_id.to_fixed_array())
+	}
+
+	pub fn get_asset_owner(asset_id: &T::AssetId) -> Option<T::AccountId> {
+        let account_id = Self::asset_owner_list(&asset_id);
+        if account_id == T::AccountId::default() {
+            None
+        } else {
+            Some(account_id)
+        }
+    }
+
+	pub fn ensure_asset_exists(asset_id: &T::AssetId) -> DispatchResult {
         if Self::get_asset_owner(asset_id).is_none() {
             return Err(Error::<T>::AssetNotExists.into());
         }
@@ -304,6 +795,115 @@ impl<T: Config> Pallet<T> {
 		is_mintable: bool,
 		is_burnable: bool,
 		description: Option<StdString>,
+		max_supply: Option<Balance>,
+		initial_supply: Balance,
+        ) -> DispatchResult {
+		if AssetPermission::<T>::contains_key(&Self::get_asset_id(symbol)){
+			let permissions = AssetPermission::<T>::get(&Self::get_asset_id(symbol));
+			ensure!(
+				permissions.0,
+				Error::<T>::AssetNotAllowedToRegister
+			);
+		}
+
+		Self::force_register_asset(
+			issuer,
+			symbol,
+			name,
+			precision,
+			is_mintable,
+			is_burnable,
+			description,
+			max_supply,
+			initial_supply,
+		)
+	}
+
+	/// Registers the asset under `AssetPermission` like `register_asset`, then reserves `AssetDeposit` from the issuer
+	#[transactional]
+	pub fn register_asset_with_deposit(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		name: &AssetName,
+		precision: BalancePrecision,
+		is_mintable: bool,
+		is_burnable: bool,
+		description: Option<StdString>,
+		max_supply: Option<Balance>,
+		initial_supply: Balance,
+        ) -> DispatchResult {
+		Self::register_asset(
+			issuer,
+			symbol,
+			name,
+			precision,
+			is_mintable,
+			is_burnable,
+			description,
+			max_supply,
+			initial_supply,
+		)?;
+
+		let deposit = T::AssetDeposit::get();
+		if deposit > 0 {
+			T::Currency::reserve(T::NativeAssetId::get(), issuer, deposit)?;
+			AssetDeposits::<T>::insert(Self::get_asset_id(symbol), deposit);
+		}
+		Ok(())
+	}
+
+	/// Registers the asset without a deposit and whatever `AssetPermission` says, share symbols are left to the pools
+	pub fn force_register_asset(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		name: &AssetName,
+		precision: BalancePrecision,
+		is_mintable: bool,
+		is_burnable: bool,
+		description: Option<StdString>,
+		max_supply: Option<Balance>,
+		initial_supply: Balance,
+        ) -> DispatchResult {
+		ensure!(
+			!crate::is_share_symbol(symbol),
+			Error::<T>::ReservedAssetSymbol
+		);
+		Self::do_register_asset(
+			issuer,
+			symbol,
+			name,
+			precision,
+			is_mintable,
+			is_burnable,
+			description,
+			max_supply,
+			initial_supply,
+		)
+	}
+
+	/// Registers the share asset of a pool, the only way to register a symbol behind `SHARE_SYMBOL_PREFIX`
+	pub fn register_share_asset(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		name: &AssetName,
+		precision: BalancePrecision,
+        ) -> DispatchResult {
+		ensure!(
+			crate::is_share_symbol(symbol),
+			Error::<T>::InvalidAssetSymbol
+		);
+		Self::do_register_asset(issuer, symbol, name, precision, true, true, None, None, 0)
+	}
+
+	fn do_register_asset(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		name: &AssetName,
+		precision: BalancePrecision,
+		is_mintable: bool,
+		is_burnable: bool,
+		description: Option<StdString>,
+		max_supply: Option<Balance>,
 		initial_supply: Balance,
         ) -> DispatchResult {
 		ensure!(
@@ -319,18 +919,16 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::InvalidPrecision
 		);
 
+		ensure!(
+			max_supply.map_or(true, |max_supply| initial_supply <= max_supply),
+			Error::<T>::ExceedsMaxSupply
+		);
+
 		let asset_id = Self::get_asset_id(symbol);
 		ensure!(
 			Self::get_asset_owner(&asset_id).is_none(),
 			Error::<T>::AssetAlreadyExists,
 		);
-		if AssetPermission::<T>::contains_key(&asset_id){
-			let permissions = AssetPermission::<T>::get(&asset_id);
-			ensure!(
-				permissions.0,
-				Error::<T>::AssetNotAllowedToRegister
-			);
-		}
 
 		frame_system::Pallet::<T>::inc_providers(&issuer);
 		AssetOwnerList::<T>::insert(asset_id.clone(), issuer.clone());
@@ -343,13 +941,47 @@ impl<T: Config> Pallet<T> {
 								 , precision: precision
 								 , is_mintable: is_mintable
 								 , is_burnable: is_burnable
-								 , description: description};
+								 , description: description
+								 , max_supply: max_supply};
 		AssetInfoData::<T>::insert(asset_id.clone(), asset_info);
 		frame_system::Pallet::<T>::inc_account_nonce(&issuer);
 		Self::deposit_event(Event::AssetRegistered(asset_id.clone(), issuer.clone()));
         Ok(())
     }
 
+	/// Only the owner destroys an asset and only once all of it is burned, the deposit goes back to the owner
+	pub fn destroy_asset(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		Self::ensure_asset_exists(&asset_id)?;
+		ensure!(
+			Self::is_asset_owner(symbol, issuer),
+			Error::<T>::InvalidOwner,
+		);
+		ensure!(
+			T::Currency::total_issuance(asset_id.clone()) == 0,
+			Error::<T>::AssetHasSupply,
+		);
+
+		frame_system::Pallet::<T>::dec_providers(&issuer).map_err(|_| Error::<T>::DecRefError)?;
+		let deposit = AssetDeposits::<T>::take(&asset_id);
+		if deposit > 0 {
+			T::Currency::unreserve(T::NativeAssetId::get(), issuer, deposit);
+		}
+		AssetOwnerList::<T>::remove(&asset_id);
+		PendingAssetOwners::<T>::remove(&asset_id);
+		FrozenAccounts::<T>::remove_prefix(&asset_id);
+		FrozenAssets::<T>::remove(&asset_id);
+		AssetMinters::<T>::remove_prefix(&asset_id);
+		AssetBurners::<T>::remove_prefix(&asset_id);
+		Allowances::<T>::remove_prefix(&asset_id);
+		AssetInfoData::<T>::remove(&asset_id);
+		Self::deposit_event(Event::AssetDestroyed(asset_id, issuer.clone(), deposit));
+		Ok(())
+	}
+
 	pub fn mint_asset(
         issuer: &T::AccountId,
         symbol: &AssetSymbol,
@@ -361,9 +993,13 @@ impl<T: Config> Pallet<T> {
 		ensure!(assetinfo.is_mintable, Error::<T>::AssetIsNotMintable);
 
 		ensure!(
-			Self::is_asset_owner(symbol, issuer),
+			Self::is_asset_owner(symbol, issuer) || AssetMinters::<T>::get(&asset_id, issuer),
 			Error::<T>::InvalidOwner,
 		);
+		ensure!(
+			assetinfo.max_supply.map_or(true, |max_supply| T::Currency::total_issuance(asset_id.clone()).saturating_add(amount) <= max_supply),
+			Error::<T>::ExceedsMaxSupply
+		);
 
 		T::Currency::deposit(asset_id.clone(), issuer, amount)?;
 		Self::deposit_event(Event::Mint(issuer.clone(), issuer.clone(), asset_id, amount));
@@ -381,9 +1017,10 @@ impl<T: Config> Pallet<T> {
 		ensure!(assetinfo.is_burnable, Error::<T>::AssetIsNotBurnable);
 
 		ensure!(
-			Self::is_asset_owner(symbol, issuer),
+			Self::is_asset_owner(symbol, issuer) || AssetBurners::<T>::get(&asset_id, issuer),
 			Error::<T>::InvalidOwner,
 		);
+		Self::ensure_not_frozen(symbol, issuer)?;
 
 		T::Currency::withdraw(asset_id.clone(), &issuer, amount)?;
 		Self::deposit_event(Event::Burn(issuer.clone(), asset_id, amount));
@@ -398,6 +1035,7 @@ impl<T: Config> Pallet<T> {
         ) -> DispatchResult {
 		let asset_id = Self::get_asset_id(&symbol);
 		Self::ensure_asset_exists(&asset_id)?;
+		Self::ensure_not_frozen(symbol, from)?;
 		ensure!(Self::free_balance(&symbol, &from).unwrap_or(0) >= amount, Error::<T>::NoEnoughBalance);
 
 		T::Currency::transfer(asset_id.clone(), from, to, amount)?;
@@ -405,6 +1043,363 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Moves the asset between any accounts, frozen or not, only for the owner of the asset
+	pub fn force_transfer_asset(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Balance,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(Self::free_balance(&symbol, &from).unwrap_or(0) >= amount, Error::<T>::NoEnoughBalance);
+
+		T::Currency::transfer(asset_id.clone(), from, to, amount)?;
+		Self::deposit_event(Event::ForceTransfer(issuer.clone(), from.clone(), to.clone(), asset_id, amount));
+		Ok(())
+	}
+
+	/// `who` mints the asset like its owner, for pallets minting without the owner key
+	pub fn add_asset_minter(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			!AssetMinters::<T>::get(&asset_id, who),
+			Error::<T>::AlreadyMinter
+		);
+
+		AssetMinters::<T>::insert(&asset_id, who, true);
+		Self::deposit_event(Event::MinterAdded(asset_id, who.clone()));
+		Ok(())
+	}
+
+	pub fn remove_asset_minter(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			AssetMinters::<T>::get(&asset_id, who),
+			Error::<T>::NotMinter
+		);
+
+		AssetMinters::<T>::remove(&asset_id, who);
+		Self::deposit_event(Event::MinterRemoved(asset_id, who.clone()));
+		Ok(())
+	}
+
+	/// `who` burns the asset it holds like the owner
+	pub fn add_asset_burner(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			!AssetBurners::<T>::get(&asset_id, who),
+			Error::<T>::AlreadyBurner
+		);
+
+		AssetBurners::<T>::insert(&asset_id, who, true);
+		Self::deposit_event(Event::BurnerAdded(asset_id, who.clone()));
+		Ok(())
+	}
+
+	pub fn remove_asset_burner(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			AssetBurners::<T>::get(&asset_id, who),
+			Error::<T>::NotBurner
+		);
+
+		AssetBurners::<T>::remove(&asset_id, who);
+		Self::deposit_event(Event::BurnerRemoved(asset_id, who.clone()));
+		Ok(())
+	}
+
+	/// Fails when the asset or the account of `who` holding it is frozen
+	pub fn ensure_not_frozen(
+        symbol: &AssetSymbol,
+        who: &T::AccountId,
+		) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		ensure!(
+			!FrozenAssets::<T>::get(&asset_id),
+			Error::<T>::AssetIsFrozen
+		);
+		ensure!(
+			!FrozenAccounts::<T>::get(&asset_id, who),
+			Error::<T>::AccountIsFrozen
+		);
+		Ok(())
+	}
+
+	pub fn freeze_account(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			!FrozenAccounts::<T>::get(&asset_id, who),
+			Error::<T>::AccountIsFrozen
+		);
+
+		FrozenAccounts::<T>::insert(&asset_id, who, true);
+		Self::deposit_event(Event::AccountFrozen(asset_id, who.clone()));
+		Ok(())
+	}
+
+	pub fn thaw_account(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		who: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			FrozenAccounts::<T>::get(&asset_id, who),
+			Error::<T>::AccountIsNotFrozen
+		);
+
+		FrozenAccounts::<T>::remove(&asset_id, who);
+		Self::deposit_event(Event::AccountThawed(asset_id, who.clone()));
+		Ok(())
+	}
+
+	pub fn freeze_asset_transfers(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			!FrozenAssets::<T>::get(&asset_id),
+			Error::<T>::AssetIsFrozen
+		);
+
+		FrozenAssets::<T>::insert(&asset_id, true);
+		Self::deposit_event(Event::AssetFrozen(asset_id));
+		Ok(())
+	}
+
+	pub fn thaw_asset_transfers(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			FrozenAssets::<T>::get(&asset_id),
+			Error::<T>::AssetIsNotFrozen
+		);
+
+		FrozenAssets::<T>::remove(&asset_id);
+		Self::deposit_event(Event::AssetThawed(asset_id));
+		Ok(())
+	}
+
+	/// Fails unless `issuer` owns the asset, which has to exist
+	fn ensure_owner_of(
+        symbol: &AssetSymbol,
+        issuer: &T::AccountId,
+		) -> Result<T::AssetId, DispatchError> {
+		let asset_id = Self::get_asset_id(&symbol);
+		Self::ensure_asset_exists(&asset_id)?;
+		ensure!(
+			Self::is_asset_owner(symbol, issuer),
+			Error::<T>::InvalidOwner,
+		);
+		Ok(asset_id)
+	}
+
+	pub fn update_asset_name(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		name: &AssetName,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			crate::is_valid_name(name),
+			Error::<T>::InvalidAssetName
+		);
+
+		let old_name = AssetInfoData::<T>::mutate(&asset_id, |asset_info| sp_std::mem::replace(&mut asset_info.name, name.clone()));
+		Self::deposit_event(Event::AssetNameUpdated(asset_id, old_name, name.clone()));
+		Ok(())
+	}
+
+	pub fn update_asset_description(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		description: Option<StdString>,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+
+		let old_description = AssetInfoData::<T>::mutate(&asset_id, |asset_info| sp_std::mem::replace(&mut asset_info.description, description.clone()));
+		Self::deposit_event(Event::AssetDescriptionUpdated(asset_id, old_description, description));
+		Ok(())
+	}
+
+	/// Nothing can be minted anymore, there is no way back
+	pub fn renounce_asset_minting(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			AssetInfoData::<T>::get(&asset_id).is_mintable,
+			Error::<T>::AssetIsNotMintable
+		);
+
+		AssetInfoData::<T>::mutate(&asset_id, |asset_info| asset_info.is_mintable = false);
+		Self::deposit_event(Event::MintingRenounced(asset_id, issuer.clone()));
+		Ok(())
+	}
+
+	/// Nothing can be burned anymore, there is no way back
+	pub fn renounce_asset_burning(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+		ensure!(
+			AssetInfoData::<T>::get(&asset_id).is_burnable,
+			Error::<T>::AssetIsNotBurnable
+		);
+
+		AssetInfoData::<T>::mutate(&asset_id, |asset_info| asset_info.is_burnable = false);
+		Self::deposit_event(Event::BurningRenounced(asset_id, issuer.clone()));
+		Ok(())
+	}
+
+	/// The ownership only moves once `new_owner` accepts it, a later proposal replaces this one
+	pub fn propose_asset_owner(
+        issuer: &T::AccountId,
+        symbol: &AssetSymbol,
+		new_owner: &T::AccountId,
+        ) -> DispatchResult {
+		let asset_id = Self::ensure_owner_of(symbol, issuer)?;
+
+		PendingAssetOwners::<T>::insert(&asset_id, new_owner.clone());
+		Self::deposit_event(Event::AssetOwnerProposed(asset_id, issuer.clone(), new_owner.clone()));
+		Ok(())
+	}
+
+	/// Makes the proposed owner the owner, the registration deposit moves along with the asset
+	#[transactional]
+	pub fn accept_asset_ownership(
+        who: &T::AccountId,
+        symbol: &AssetSymbol,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		let old_owner = Self::get_asset_owner(&asset_id).ok_or(Error::<T>::AssetNotExists)?;
+		ensure!(
+			PendingAssetOwners::<T>::get(&asset_id).as_ref() == Some(who),
+			Error::<T>::NotPendingOwner
+		);
+
+		let deposit = AssetDeposits::<T>::get(&asset_id);
+		if deposit > 0 {
+			T::Currency::reserve(T::NativeAssetId::get(), who, deposit)?;
+			T::Currency::unreserve(T::NativeAssetId::get(), &old_owner, deposit);
+		}
+		frame_system::Pallet::<T>::inc_providers(who);
+		frame_system::Pallet::<T>::dec_providers(&old_owner).map_err(|_| Error::<T>::DecRefError)?;
+		AssetOwnerList::<T>::insert(&asset_id, who.clone());
+		PendingAssetOwners::<T>::remove(&asset_id);
+		Self::deposit_event(Event::AssetOwnerChanged(asset_id, old_owner, who.clone()));
+		Ok(())
+	}
+
+	pub fn allowance(
+        symbol: &AssetSymbol,
+        owner: &T::AccountId,
+        spender: &T::AccountId,
+		) -> Balance {
+		Allowances::<T>::get(Self::get_asset_id(symbol), (owner.clone(), spender.clone()))
+	}
+
+	/// Lets `spender` transfer up to `amount` from `owner`, replacing the previous allowance
+	pub fn approve_asset(
+        owner: &T::AccountId,
+        symbol: &AssetSymbol,
+		spender: &T::AccountId,
+		amount: Balance,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		Self::ensure_asset_exists(&asset_id)?;
+
+		Self::set_allowance(&asset_id, owner, spender, amount);
+		Ok(())
+	}
+
+	pub fn increase_asset_allowance(
+        owner: &T::AccountId,
+        symbol: &AssetSymbol,
+		spender: &T::AccountId,
+		amount: Balance,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		Self::ensure_asset_exists(&asset_id)?;
+
+		let allowance = Self::allowance(symbol, owner, spender);
+		Self::set_allowance(&asset_id, owner, spender, allowance.saturating_add(amount));
+		Ok(())
+	}
+
+	pub fn decrease_asset_allowance(
+        owner: &T::AccountId,
+        symbol: &AssetSymbol,
+		spender: &T::AccountId,
+		amount: Balance,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		Self::ensure_asset_exists(&asset_id)?;
+
+		let allowance = Self::allowance(symbol, owner, spender);
+		ensure!(allowance >= amount, Error::<T>::NoEnoughAllowance);
+		Self::set_allowance(&asset_id, owner, spender, allowance - amount);
+		Ok(())
+	}
+
+	/// Transfers from `owner` on behalf of `spender`, spending the allowance `owner` gave it
+	pub fn transfer_asset_from(
+        spender: &T::AccountId,
+        symbol: &AssetSymbol,
+		owner: &T::AccountId,
+		to: &T::AccountId,
+		amount: Balance,
+        ) -> DispatchResult {
+		let asset_id = Self::get_asset_id(&symbol);
+		let allowance = Self::allowance(symbol, owner, spender);
+		ensure!(allowance >= amount, Error::<T>::NoEnoughAllowance);
+
+		Self::transfer_asset(owner, symbol, to, amount)?;
+		Self::set_allowance(&asset_id, owner, spender, allowance - amount);
+		Ok(())
+	}
+
+	fn set_allowance(
+        asset_id: &T::AssetId,
+        owner: &T::AccountId,
+		spender: &T::AccountId,
+		amount: Balance,
+        ) {
+		if amount == 0 {
+			Allowances::<T>::remove(asset_id, (owner.clone(), spender.clone()));
+		}
+		else {
+			Allowances::<T>::insert(asset_id, (owner.clone(), spender.clone()), amount);
+		}
+		Self::deposit_event(Event::Approval(owner.clone(), spender.clone(), asset_id.clone(), amount));
+	}
+
 	pub fn is_asset_mintable(symbol: &AssetSymbol) -> bool {
 		let asset_id = Self::get_asset_id(&symbol);
 		if Self::get_asset_owner(&asset_id).is_none() {
@@ -0,0 +1,37 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+use super::*;
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+
+/// `AssetInfo` as stored by `Releases::V1`
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct AssetInfoV1 {
+	pub symbol: AssetSymbol,
+	pub name: AssetName,
+	pub precision: BalancePrecision,
+	pub is_mintable: bool,
+	pub is_burnable: bool,
+	pub description: Option<StdString>,
+}
+
+/// Appends `max_supply` to the info of every asset, assets registered before it existed have no cap
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let mut migrated: Weight = 0;
+	AssetInfoData::<T>::translate::<AssetInfoV1, _>(|_, old| {
+		migrated += 1;
+		Some(AssetInfo{symbol: old.symbol
+					 , name: old.name
+					 , precision: old.precision
+					 , is_mintable: old.is_mintable
+					 , is_burnable: old.is_burnable
+					 , description: old.description
+					 , max_supply: None
+					 })
+	});
+	StorageVersion::<T>::put(Releases::V2);
+	T::DbWeight::get().reads_writes(migrated + 1, migrated + 1)
+}
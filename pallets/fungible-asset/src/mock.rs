@@ -50,6 +50,7 @@ parameter_types! {
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(80);
 
 	pub const GetBaseAssetId: AssetId = DEFAULT_SYMBOL;
+	pub const AssetDeposit: Balance = 100;
 	pub const ExistentialDeposit: u128 = 0;
 }
 
@@ -116,6 +117,8 @@ impl crate::Config for Runtime {
     type Event = Event;
     type AssetId = AssetId;
     type Currency = currencies::Module<Runtime>;
+    type NativeAssetId = GetBaseAssetId;
+    type AssetDeposit = AssetDeposit;
 }
 
 pub struct ExtBuilder {
@@ -125,7 +128,7 @@ pub struct ExtBuilder {
 impl Default for ExtBuilder {
     fn default() -> Self {
         Self {
-            endowed_accounts: vec![(ALICE, DEFAULT_SYMBOL, 0), (BOB, DEFAULT_SYMBOL, 0)],
+            endowed_accounts: vec![(ALICE, DEFAULT_SYMBOL, 1_000), (BOB, DEFAULT_SYMBOL, 0)],
         }
     }
 }
@@ -7,7 +7,9 @@ mod tests {
     use crate::mock::*;
     use crate::Error;
     use frame_support::{assert_noop, assert_ok};
+	use frame_support::traits::OnRuntimeUpgrade;
     use sp_runtime::traits::Zero;
+	use sp_runtime::DispatchError;
 
 	use base::*;
 
@@ -26,6 +28,7 @@ mod tests {
 				true,
 				true,
 				None,
+				None,
                 Balance::zero(),
             ));
 
@@ -37,6 +40,7 @@ mod tests {
 					true,
 					true,
 					None,
+					None,
 					Balance::zero(),
 				),
 				Error::<Runtime>::AssetAlreadyExists
@@ -67,6 +71,7 @@ mod tests {
 				true,
 				true,
 				None,
+				None,
                 Balance::from(256u64),
             ));
 			assert_eq!(
@@ -88,6 +93,7 @@ mod tests {
 				true,
 				true,
 				None,
+				None,
                 Balance::zero(),
             ));
 
@@ -123,6 +129,7 @@ mod tests {
 				true,
 				true,
 				None,
+				None,
                 Balance::zero(),
             ));
 
@@ -155,6 +162,7 @@ mod tests {
 				true,
 				true,
 				None,
+				None,
                 Balance::zero(),
             ));
 
@@ -189,4 +197,277 @@ mod tests {
 		});
 	}
 
+	#[test]
+    fn test_register_with_deposit() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let name = AssetName::from_string("polkadot");
+			assert_ok!(FungibleAsset::register(Origin::signed(ALICE), TEST_SYMBOL, name, 18, true, true, None, None, 0));
+			assert_eq!(Balances::free_balance(&ALICE), 900);
+			assert_eq!(Balances::reserved_balance(&ALICE), 100);
+			assert_eq!(FungibleAsset::asset_deposits(&TEST_SYMBOL), 100);
+
+			let symbol = AssetSymbol::from_const_string("KSM");
+			assert_noop!(FungibleAsset::register(Origin::signed(BOB), symbol, name, 18, true, true, None, None, 0),
+				pallet_balances::Error::<Runtime>::InsufficientBalance
+			);
+			assert_ok!(FungibleAsset::set_asset_permission(Origin::root(), symbol, false));
+			assert_noop!(FungibleAsset::register(Origin::signed(ALICE), symbol, name, 18, true, true, None, None, 0),
+				Error::<Runtime>::AssetNotAllowedToRegister
+			);
+			assert_noop!(FungibleAsset::force_register(Origin::signed(ALICE), BOB, symbol, name, 18, true, true, None, None, 0),
+				DispatchError::BadOrigin
+			);
+			assert_ok!(FungibleAsset::force_register(Origin::root(), BOB, symbol, name, 18, true, true, None, None, 0));
+			// share symbols belong to the pools, not even root registers them
+			assert_noop!(FungibleAsset::force_register(Origin::root(), BOB, AssetSymbol::from_const_string("LP-DOT"), name, 18, true, true, None, None, 0),
+				Error::<Runtime>::ReservedAssetSymbol
+			);
+			assert!(FungibleAsset::is_asset_owner(&symbol, &BOB));
+			assert_eq!(FungibleAsset::asset_deposits(&symbol), 0);
+		});
+	}
+
+	#[test]
+    fn test_destroy_asset() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register(Origin::signed(ALICE), TEST_SYMBOL, AssetName::from_string("polkadot"), 18, true, true, None, None, 10));
+			assert_noop!(FungibleAsset::destroy(Origin::signed(BOB), TEST_SYMBOL), Error::<Runtime>::InvalidOwner);
+			assert_noop!(FungibleAsset::destroy(Origin::signed(ALICE), TEST_SYMBOL), Error::<Runtime>::AssetHasSupply);
+			assert_ok!(FungibleAsset::approve(Origin::signed(ALICE), TEST_SYMBOL, BOB, 30));
+
+			assert_ok!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10));
+			assert_ok!(FungibleAsset::destroy(Origin::signed(ALICE), TEST_SYMBOL));
+			// an approval must not carry over to an asset registered again under the same symbol
+			assert!(crate::Allowances::<Runtime>::contains_key(&TEST_SYMBOL, (ALICE, BOB)) == false);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000);
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert!(FungibleAsset::is_asset_existed(&TEST_SYMBOL) == false);
+			assert_noop!(FungibleAsset::destroy(Origin::signed(ALICE), TEST_SYMBOL), Error::<Runtime>::AssetNotExists);
+		});
+	}
+
+	#[test]
+    fn test_allowances() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_noop!(FungibleAsset::approve(Origin::signed(ALICE), TEST_SYMBOL, BOB, 30),
+				Error::<Runtime>::AssetNotExists
+			);
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &AssetName::from_string("polkadot"), 18, true, true, None, None, 100));
+
+			assert_ok!(FungibleAsset::approve(Origin::signed(ALICE), TEST_SYMBOL, BOB, 30));
+			assert_ok!(FungibleAsset::transfer_from(Origin::signed(BOB), TEST_SYMBOL, ALICE, BOB, 20));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL, &BOB), Ok(20));
+			assert_eq!(FungibleAsset::allowance(&TEST_SYMBOL, &ALICE, &BOB), 10);
+			assert_noop!(FungibleAsset::transfer_from(Origin::signed(BOB), TEST_SYMBOL, ALICE, BOB, 11),
+				Error::<Runtime>::NoEnoughAllowance
+			);
+			assert_noop!(FungibleAsset::transfer_asset_from(&ALICE, &TEST_SYMBOL, &BOB, &ALICE, 1),
+				Error::<Runtime>::NoEnoughAllowance
+			);
+
+			assert_ok!(FungibleAsset::increase_allowance(Origin::signed(ALICE), TEST_SYMBOL, BOB, 100));
+			assert_noop!(FungibleAsset::transfer_asset_from(&BOB, &TEST_SYMBOL, &ALICE, &BOB, 100),
+				Error::<Runtime>::NoEnoughBalance
+			);
+			assert_noop!(FungibleAsset::decrease_allowance(Origin::signed(ALICE), TEST_SYMBOL, BOB, 111),
+				Error::<Runtime>::NoEnoughAllowance
+			);
+			assert_ok!(FungibleAsset::decrease_allowance(Origin::signed(ALICE), TEST_SYMBOL, BOB, 110));
+			assert_eq!(FungibleAsset::allowance(&TEST_SYMBOL, &ALICE, &BOB), 0);
+			assert!(crate::Allowances::<Runtime>::contains_key(&TEST_SYMBOL, (ALICE, BOB)) == false);
+		});
+	}
+
+	#[test]
+    fn test_update_asset_metadata() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &AssetName::from_string("polkadot"), 18, true, true, None, None, 100));
+			let name = AssetName::from_string("dot");
+			assert_noop!(FungibleAsset::update_name(Origin::signed(BOB), TEST_SYMBOL, name),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_ok!(FungibleAsset::update_name(Origin::signed(ALICE), TEST_SYMBOL, name));
+			assert_ok!(FungibleAsset::update_description(Origin::signed(ALICE), TEST_SYMBOL, Some(StdString::from_string("relay chain"))));
+			let asset_info = FungibleAsset::asset_info_data(&TEST_SYMBOL);
+			assert_eq!((asset_info.name, asset_info.description), (name, Some(StdString::from_string("relay chain"))));
+
+			assert_ok!(FungibleAsset::renounce_minting(Origin::signed(ALICE), TEST_SYMBOL));
+			assert_noop!(FungibleAsset::renounce_minting(Origin::signed(ALICE), TEST_SYMBOL),
+				Error::<Runtime>::AssetIsNotMintable
+			);
+			assert_noop!(FungibleAsset::mint_asset(&ALICE, &TEST_SYMBOL, 10),
+				Error::<Runtime>::AssetIsNotMintable
+			);
+			assert_ok!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10));
+			assert_ok!(FungibleAsset::renounce_burning(Origin::signed(ALICE), TEST_SYMBOL));
+			assert_noop!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10),
+				Error::<Runtime>::AssetIsNotBurnable
+			);
+		});
+	}
+
+	#[test]
+    fn test_transfer_ownership() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register(Origin::signed(ALICE), TEST_SYMBOL, AssetName::from_string("polkadot"), 18, true, true, None, None, 0));
+			assert_noop!(FungibleAsset::propose_owner(Origin::signed(BOB), TEST_SYMBOL, BOB),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_noop!(FungibleAsset::accept_ownership(Origin::signed(BOB), TEST_SYMBOL),
+				Error::<Runtime>::NotPendingOwner
+			);
+			assert_ok!(FungibleAsset::propose_owner(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_eq!(FungibleAsset::pending_asset_owners(&TEST_SYMBOL), Some(BOB));
+			assert!(FungibleAsset::is_asset_owner(&TEST_SYMBOL, &ALICE));
+
+			// the registration deposit moves along with the asset
+			assert_noop!(FungibleAsset::accept_ownership(Origin::signed(BOB), TEST_SYMBOL),
+				pallet_balances::Error::<Runtime>::InsufficientBalance
+			);
+			assert_ok!(Balances::transfer(Origin::signed(ALICE), BOB, 200));
+			assert_ok!(FungibleAsset::accept_ownership(Origin::signed(BOB), TEST_SYMBOL));
+			assert!(FungibleAsset::is_asset_owner(&TEST_SYMBOL, &BOB));
+			assert_eq!(FungibleAsset::pending_asset_owners(&TEST_SYMBOL), None);
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert_eq!(Balances::reserved_balance(&BOB), 100);
+			assert_noop!(FungibleAsset::update_name(Origin::signed(ALICE), TEST_SYMBOL, AssetName::from_string("dot")),
+				Error::<Runtime>::InvalidOwner
+			);
+		});
+	}
+
+	#[test]
+    fn test_freeze() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &AssetName::from_string("polkadot"), 18, true, true, None, None, 100));
+			assert_ok!(FungibleAsset::transfer_asset(&ALICE, &TEST_SYMBOL, &BOB, 50));
+			assert_noop!(FungibleAsset::freeze(Origin::signed(BOB), TEST_SYMBOL, BOB),
+				Error::<Runtime>::InvalidOwner
+			);
+
+			assert_ok!(FungibleAsset::freeze(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_noop!(FungibleAsset::freeze(Origin::signed(ALICE), TEST_SYMBOL, BOB),
+				Error::<Runtime>::AccountIsFrozen
+			);
+			assert_noop!(FungibleAsset::transfer_asset(&BOB, &TEST_SYMBOL, &ALICE, 10),
+				Error::<Runtime>::AccountIsFrozen
+			);
+			assert_ok!(FungibleAsset::transfer_asset(&ALICE, &TEST_SYMBOL, &BOB, 10));
+
+			// the issuer can still move the balance of a frozen account
+			assert_noop!(FungibleAsset::force_transfer(Origin::signed(BOB), TEST_SYMBOL, BOB, ALICE, 10),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_ok!(FungibleAsset::force_transfer(Origin::signed(ALICE), TEST_SYMBOL, BOB, ALICE, 10));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL, &BOB), Ok(50));
+			assert_ok!(FungibleAsset::thaw(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_noop!(FungibleAsset::thaw(Origin::signed(ALICE), TEST_SYMBOL, BOB),
+				Error::<Runtime>::AccountIsNotFrozen
+			);
+			assert_ok!(FungibleAsset::transfer_asset(&BOB, &TEST_SYMBOL, &ALICE, 10));
+
+			assert_ok!(FungibleAsset::freeze_asset(Origin::signed(ALICE), TEST_SYMBOL));
+			assert_noop!(FungibleAsset::transfer_asset(&ALICE, &TEST_SYMBOL, &BOB, 10),
+				Error::<Runtime>::AssetIsFrozen
+			);
+			assert_noop!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10),
+				Error::<Runtime>::AssetIsFrozen
+			);
+			assert_ok!(FungibleAsset::thaw_asset(Origin::signed(ALICE), TEST_SYMBOL));
+			assert_noop!(FungibleAsset::thaw_asset(Origin::signed(ALICE), TEST_SYMBOL),
+				Error::<Runtime>::AssetIsNotFrozen
+			);
+			assert_ok!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10));
+		});
+	}
+
+	#[test]
+    fn test_max_supply() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let name = AssetName::from_string("polkadot");
+			assert_noop!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &name, 18, true, true, None, Some(100), 101),
+				Error::<Runtime>::ExceedsMaxSupply
+			);
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &name, 18, true, true, None, Some(100), 60));
+			assert_eq!(FungibleAsset::asset_info_data(&TEST_SYMBOL).max_supply, Some(100));
+
+			assert_ok!(FungibleAsset::mint_asset(&ALICE, &TEST_SYMBOL, 40));
+			assert_noop!(FungibleAsset::mint_asset(&ALICE, &TEST_SYMBOL, 1),
+				Error::<Runtime>::ExceedsMaxSupply
+			);
+			// burning makes room below the cap again
+			assert_ok!(FungibleAsset::burn_asset(&ALICE, &TEST_SYMBOL, 10));
+			assert_ok!(FungibleAsset::mint_asset(&ALICE, &TEST_SYMBOL, 10));
+		});
+	}
+
+	#[test]
+    fn test_minters_and_burners() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL, &AssetName::from_string("polkadot"), 18, true, true, None, None, 0));
+			assert_noop!(FungibleAsset::mint_asset(&BOB, &TEST_SYMBOL, 10),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_noop!(FungibleAsset::add_minter(Origin::signed(BOB), TEST_SYMBOL, BOB),
+				Error::<Runtime>::InvalidOwner
+			);
+
+			assert_ok!(FungibleAsset::add_minter(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_noop!(FungibleAsset::add_minter(Origin::signed(ALICE), TEST_SYMBOL, BOB),
+				Error::<Runtime>::AlreadyMinter
+			);
+			assert_ok!(FungibleAsset::mint_asset(&BOB, &TEST_SYMBOL, 10));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL, &BOB), Ok(10));
+			assert_noop!(FungibleAsset::burn_asset(&BOB, &TEST_SYMBOL, 5),
+				Error::<Runtime>::InvalidOwner
+			);
+
+			assert_ok!(FungibleAsset::add_burner(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_ok!(FungibleAsset::burn_asset(&BOB, &TEST_SYMBOL, 5));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL, &BOB), Ok(5));
+
+			assert_ok!(FungibleAsset::remove_minter(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_noop!(FungibleAsset::remove_minter(Origin::signed(ALICE), TEST_SYMBOL, BOB),
+				Error::<Runtime>::NotMinter
+			);
+			assert_noop!(FungibleAsset::mint_asset(&BOB, &TEST_SYMBOL, 10),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_ok!(FungibleAsset::remove_burner(Origin::signed(ALICE), TEST_SYMBOL, BOB));
+			assert_noop!(FungibleAsset::remove_burner(Origin::signed(ALICE), TEST_SYMBOL, BOB),
+				Error::<Runtime>::NotBurner
+			);
+		});
+	}
+
+	#[test]
+	fn test_migrate_to_v2() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let legacy_asset_info = crate::migration::AssetInfoV1{symbol: TEST_SYMBOL
+				, name: AssetName::from_string("polkadot")
+				, precision: 18
+				, is_mintable: true
+				, is_burnable: false
+				, description: None
+				};
+			frame_support::storage::unhashed::put(&crate::AssetInfoData::<Runtime>::hashed_key_for(&TEST_SYMBOL), &legacy_asset_info);
+			crate::StorageVersion::<Runtime>::put(crate::Releases::V1);
+
+			<FungibleAsset as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(crate::StorageVersion::<Runtime>::get(), crate::Releases::V2);
+			let asset_info = FungibleAsset::asset_info_data(&TEST_SYMBOL);
+			assert_eq!((asset_info.name, asset_info.is_burnable, asset_info.max_supply),
+				(AssetName::from_string("polkadot"), false, None)
+			);
+		});
+	}
 }
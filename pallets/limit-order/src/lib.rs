@@ -0,0 +1,339 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{ensure, transactional, RuntimeDebug};
+use frame_support::dispatch::DispatchResult;
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use frame_system::ensure_signed;
+use sp_std::vec::Vec;
+use traits::MultiReservableCurrency;
+
+pub use base::*;
+
+type FungibleAsset<T> = pallet_fungible_asset::Pallet<T>;
+type PoolAmm<T> = pallet_pool_amm::Pallet<T>;
+type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
+
+pub type OrderId = u64;
+
+/// Swaps `amount_in` of `asset_in` in the pool once it returns at least `min_amount_out` of `asset_out`
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct LimitOrder<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	pub pid: PoolId,
+	pub asset_in: AssetSymbol,
+	pub amount_in: Balance,
+	pub asset_out: AssetSymbol,
+	pub min_amount_out: Balance,
+	/// last block the order can be filled in
+	pub expires_at: Option<BlockNumber>,
+	/// native asset reserved while the order is open
+	pub deposit: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+    pub trait Config:
+        frame_system::Config + pallet_pool_amm::Config
+    {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Bounds the orders checked at the start of every block
+		#[pallet::constant]
+		type MaxOpenOrders: Get<u32>;
+
+		/// Keeps a single account from taking up all of `MaxOpenOrders`
+		#[pallet::constant]
+		type MaxOrdersPerAccount: Get<u32>;
+
+		/// Native asset reserved for each open order, given back when it is filled, cancelled or expired
+		#[pallet::constant]
+		type OrderDeposit: Get<Balance>;
+
+		/// Bounds the orders tried against the pools in one block, the others get their turn in the next blocks
+		#[pallet::constant]
+		type MaxFillAttemptsPerBlock: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Expires the orders past their last block and fills the ones the pools can pay, trying at most
+		/// `MaxFillAttemptsPerBlock` of them in order of their ids from where the last block stopped
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut orders = LimitOrders::<T>::iter().collect::<Vec<_>>();
+			orders.sort_by_key(|(order_id, _)| *order_id);
+			let next_fill_attempt = NextFillAttempt::<T>::get();
+			let start = orders.iter().position(|(order_id, _)| *order_id >= next_fill_attempt).unwrap_or(0);
+			orders.rotate_left(start);
+
+			let max_attempts = T::MaxFillAttemptsPerBlock::get() as Weight;
+			let mut writes: Weight = 0;
+			let mut attempts: Weight = 0;
+			let mut swaps: Weight = 0;
+			for (order_id, order) in &orders {
+				if order.expires_at.map_or(false, |expires_at| now > expires_at) {
+					Self::expire_order(*order_id, order);
+					writes += 5;
+				}
+				else if attempts < max_attempts {
+					attempts += 1;
+					NextFillAttempt::<T>::put(order_id + 1);
+					if Self::is_fillable(order) {
+						// a swap failing on the way costs about as much as a filled one, it is only rolled back
+						let _ = Self::fill_order(*order_id, order);
+						swaps += 1;
+					}
+				}
+			}
+			swaps * 2_000_000 + T::DbWeight::get().reads_writes(
+				orders.len() as Weight + attempts * 2 + swaps * 4 + 2,
+				writes + swaps * 9 + 1,
+			)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			min_amount_out: Balance,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::place_limit_order(
+				&who,
+				&pid,
+				&asset_in,
+				amount_in,
+				&asset_out,
+				min_amount_out,
+				expires_at,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn cancel_order(
+			origin: OriginFor<T>,
+			order_id: OrderId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::cancel_limit_order(
+				&who,
+				order_id,
+			)?;
+			Ok(().into())
+		}
+	}
+
+	#[pallet::storage]
+    #[pallet::getter(fn limit_orders)]
+    pub type LimitOrders<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        OrderId,
+		LimitOrder<T::AccountId, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn open_orders_count)]
+    pub type OpenOrdersCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+    #[pallet::getter(fn account_orders_count)]
+    pub type AccountOrdersCount<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+		u32,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn next_order_id)]
+    pub type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+	/// the lowest order id the next block starts its fill attempts from
+	#[pallet::storage]
+    #[pallet::getter(fn next_fill_attempt)]
+    pub type NextFillAttempt<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+	#[pallet::error]
+    pub enum Error<T> {
+		ZeroAmount,
+		SymbolNotExistsInPool,
+		InvalidExpiry,
+		TooManyOrders,
+		TooManyAccountOrders,
+		OrderNotExists,
+		NotOrderOwner,
+	}
+
+	#[pallet::event]
+    #[pallet::metadata(AccountIdOf<T> = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+		/// order, owner, pool, asset and amount in, asset and minimum amount out
+		OrderPlaced(OrderId, AccountIdOf<T>, PoolId, AssetSymbol, Balance, AssetSymbol, Balance),
+		/// order, owner, amount in and amount out
+		OrderFilled(OrderId, AccountIdOf<T>, Balance, Balance),
+		OrderCancelled(OrderId, AccountIdOf<T>),
+		OrderExpired(OrderId, AccountIdOf<T>),
+	}
+}
+
+pub use pallet::*;
+
+impl<T: Config> Pallet<T> {
+	/// Reserves `amount_in` and the order deposit until the order is filled at the start of a block, cancelled or expired
+	#[transactional]
+	pub fn place_limit_order(
+		who: &T::AccountId,
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		min_amount_out: Balance,
+		expires_at: Option<T::BlockNumber>,
+	) -> DispatchResult {
+		ensure!(
+			amount_in > 0 && min_amount_out > 0,
+			Error::<T>::ZeroAmount
+		);
+		ensure!(
+			PoolAmm::<T>::is_swappable(pid, asset_in, asset_out),
+			Error::<T>::SymbolNotExistsInPool
+		);
+		ensure!(
+			expires_at.map_or(true, |expires_at| expires_at >= <frame_system::Pallet<T>>::block_number()),
+			Error::<T>::InvalidExpiry
+		);
+		ensure!(
+			OpenOrdersCount::<T>::get() < T::MaxOpenOrders::get(),
+			Error::<T>::TooManyOrders
+		);
+		ensure!(
+			AccountOrdersCount::<T>::get(who) < T::MaxOrdersPerAccount::get(),
+			Error::<T>::TooManyAccountOrders
+		);
+
+		FungibleAsset::<T>::ensure_not_frozen(asset_in, who)?;
+		T::Currency::reserve(FungibleAsset::<T>::get_asset_id(asset_in), who, amount_in)?;
+		let deposit = T::OrderDeposit::get();
+		if deposit > 0 {
+			T::Currency::reserve(T::NativeAssetId::get(), who, deposit)?;
+		}
+		let order_id = NextOrderId::<T>::get();
+		NextOrderId::<T>::put(order_id + 1);
+		OpenOrdersCount::<T>::mutate(|count| *count += 1);
+		AccountOrdersCount::<T>::mutate(who, |count| *count += 1);
+		LimitOrders::<T>::insert(order_id, LimitOrder {
+			owner: who.clone(),
+			pid: pid.clone(),
+			asset_in: asset_in.clone(),
+			amount_in,
+			asset_out: asset_out.clone(),
+			min_amount_out,
+			expires_at,
+			deposit,
+		});
+		Self::deposit_event(Event::OrderPlaced(order_id, who.clone(), pid.clone(), asset_in.clone(), amount_in, asset_out.clone(), min_amount_out));
+		Ok(())
+	}
+
+	pub fn cancel_limit_order(
+		who: &T::AccountId,
+		order_id: OrderId,
+	) -> DispatchResult {
+		let order = LimitOrders::<T>::get(order_id).ok_or(Error::<T>::OrderNotExists)?;
+		ensure!(
+			order.owner == *who,
+			Error::<T>::NotOrderOwner
+		);
+
+		Self::close_order(order_id, &order);
+		Self::deposit_event(Event::OrderCancelled(order_id, who.clone()));
+		Ok(())
+	}
+
+	/// Whether the pool would pay the order its minimum output now
+	pub fn is_fillable(order: &LimitOrder<T::AccountId, T::BlockNumber>) -> bool {
+		PoolAmm::<T>::is_swappable(&order.pid, &order.asset_in, &order.asset_out)
+			&& PoolAmm::<T>::pool_statuses(&order.pid).can_swap()
+			&& PoolAmm::<T>::get_swap_return_asset(&order.pid, &order.asset_in, order.amount_in, &order.asset_out) >= order.min_amount_out
+	}
+
+	/// Swaps the reserved input of the order, nothing changes when the swap fails
+	#[transactional]
+	fn fill_order(
+		order_id: OrderId,
+		order: &LimitOrder<T::AccountId, T::BlockNumber>,
+	) -> DispatchResult {
+		T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(&order.asset_in), &order.owner, order.amount_in);
+		let amount_out = PoolAmm::<T>::get_swap_return_asset(&order.pid, &order.asset_in, order.amount_in, &order.asset_out);
+		PoolAmm::<T>::swap_asset(&order.owner, &order.pid, &order.asset_in, order.amount_in, &order.asset_out, order.min_amount_out)?;
+
+		Self::remove_order(order_id, order);
+		Self::deposit_event(Event::OrderFilled(order_id, order.owner.clone(), order.amount_in, amount_out));
+		Ok(())
+	}
+
+	fn expire_order(
+		order_id: OrderId,
+		order: &LimitOrder<T::AccountId, T::BlockNumber>,
+	) {
+		Self::close_order(order_id, order);
+		Self::deposit_event(Event::OrderExpired(order_id, order.owner.clone()));
+	}
+
+	/// Returns the reserved input to the owner and removes the order
+	fn close_order(
+		order_id: OrderId,
+		order: &LimitOrder<T::AccountId, T::BlockNumber>,
+	) {
+		T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(&order.asset_in), &order.owner, order.amount_in);
+		Self::remove_order(order_id, order);
+	}
+
+	/// Gives the deposit back and removes the order, the reserved input is left to the caller
+	fn remove_order(
+		order_id: OrderId,
+		order: &LimitOrder<T::AccountId, T::BlockNumber>,
+	) {
+		if order.deposit > 0 {
+			T::Currency::unreserve(T::NativeAssetId::get(), &order.owner, order.deposit);
+		}
+		LimitOrders::<T>::remove(order_id);
+		OpenOrdersCount::<T>::mutate(|count| *count -= 1);
+		AccountOrdersCount::<T>::mutate_exists(&order.owner, |count| {
+			*count = count.map(|open| open - 1).filter(|open| *open > 0);
+		});
+	}
+}
@@ -0,0 +1,109 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+mod tests {
+    use crate::mock::*;
+    use crate::Error;
+    use frame_support::{assert_noop, assert_ok};
+	use frame_support::traits::OnInitialize;
+	use sp_std::collections::btree_map::BTreeMap;
+
+	use base::*;
+
+	fn create_pool() {
+		let mut symbol_data: SymbolData = BTreeMap::new();
+		symbol_data.insert(TEST_SYMBOL1.clone(), 10_000);
+		symbol_data.insert(TEST_SYMBOL2.clone(), 10_000);
+		assert_ok!(PoolAmm::register_pool(&ALICE, &POOL1, 30, 10, &symbol_data, None));
+	}
+
+	#[test]
+	fn test_place_and_cancel() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool();
+			System::set_block_number(5);
+			assert_noop!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 0, &TEST_SYMBOL2, 200, None),
+				Error::<Runtime>::ZeroAmount);
+			assert_noop!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL3, 200, None),
+				Error::<Runtime>::SymbolNotExistsInPool);
+			assert_noop!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 200, Some(4)),
+				Error::<Runtime>::InvalidExpiry);
+
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 200, None));
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, 200, Some(5)));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE));
+			assert_eq!(Balances::reserved_balance(&BOB), 20);
+			assert_noop!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 200, None),
+				Error::<Runtime>::TooManyAccountOrders);
+			assert_ok!(LimitOrder::place_limit_order(&ALICE, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 200, None));
+			assert_noop!(LimitOrder::place_limit_order(&ALICE, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 200, None),
+				Error::<Runtime>::TooManyOrders);
+
+			assert_noop!(LimitOrder::cancel_limit_order(&ALICE, 0), Error::<Runtime>::NotOrderOwner);
+			assert_ok!(LimitOrder::cancel_limit_order(&BOB, 0));
+			assert_noop!(LimitOrder::cancel_limit_order(&BOB, 0), Error::<Runtime>::OrderNotExists);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE));
+			assert_eq!(Balances::reserved_balance(&BOB), 10);
+			assert_eq!(LimitOrder::open_orders_count(), 2);
+			assert_eq!(LimitOrder::account_orders_count(&BOB), 1);
+		});
+	}
+
+	#[test]
+	fn test_fill_and_expire() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool();
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 110, None));
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 1_000, Some(3)));
+
+			// not filled while the pool pays less than the limit
+			assert!(PoolAmm::get_swap_return_asset(&POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2) < 110);
+			LimitOrder::on_initialize(1);
+			assert!(LimitOrder::limit_orders(0).is_some());
+
+			assert_ok!(PoolAmm::swap_asset(&ALICE, &POOL1, &TEST_SYMBOL2, 1_000, &TEST_SYMBOL1, 1));
+			let amount_out = PoolAmm::get_swap_return_asset(&POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2);
+			assert!(amount_out >= 110);
+			LimitOrder::on_initialize(2);
+			assert_eq!(LimitOrder::limit_orders(0), None);
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + amount_out));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL1));
+
+			// the other order can still be filled in its last block, then it expires
+			LimitOrder::on_initialize(3);
+			assert!(LimitOrder::limit_orders(1).is_some());
+			LimitOrder::on_initialize(4);
+			assert_eq!(LimitOrder::limit_orders(1), None);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(LimitOrder::open_orders_count(), 0);
+			assert_eq!(LimitOrder::account_orders_count(&BOB), 0);
+			assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+		});
+	}
+
+	#[test]
+	fn test_fill_attempts_per_block() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool();
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 1_000, None));
+			assert_ok!(LimitOrder::place_limit_order(&BOB, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 1_000, None));
+			assert_ok!(LimitOrder::place_limit_order(&ALICE, &POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 1, None));
+
+			// the orders the pools can not pay take up the attempts of the block, the last one waits its turn
+			LimitOrder::on_initialize(1);
+			assert!(LimitOrder::limit_orders(2).is_some());
+			assert_eq!(LimitOrder::next_fill_attempt(), 2);
+			LimitOrder::on_initialize(2);
+			assert_eq!(LimitOrder::limit_orders(2), None);
+			assert_eq!(LimitOrder::next_fill_attempt(), 1);
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		});
+	}
+}
@@ -0,0 +1,666 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{ensure, transactional, RuntimeDebug};
+use frame_support::dispatch::DispatchResult;
+use frame_support::traits::{BalanceStatus, Get};
+use frame_support::weights::Weight;
+use frame_system::ensure_signed;
+use sp_runtime::{FixedPointNumber, FixedU128};
+use sp_runtime::helpers_128bit::multiply_by_rational;
+use sp_std::vec::Vec;
+use sp_std::cmp::{min, Ordering};
+use traits::MultiReservableCurrency;
+
+pub use base::*;
+
+type FungibleAsset<T> = pallet_fungible_asset::Pallet<T>;
+type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
+
+pub type OrderId = u64;
+
+/// most orders resting on one side of a book
+pub const MAX_ORDERS_PER_SIDE: usize = 1_000;
+/// most resting orders filled by one order or swap
+pub const MAX_FILLS_PER_CALL: usize = 50;
+/// weight of decoding, searching and encoding a full side of a book
+pub const QUEUE_WEIGHT: Weight = 10_000 * MAX_ORDERS_PER_SIDE as Weight;
+
+/// The side of the book an order rests on
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+	/// buys the base asset with the quote asset
+	Bid,
+	/// sells the base asset for the quote asset
+	Ask,
+}
+
+impl OrderSide {
+	pub fn opposite(&self) -> Self {
+		match self {
+			OrderSide::Bid => OrderSide::Ask,
+			OrderSide::Ask => OrderSide::Bid,
+		}
+	}
+}
+
+/// An order book trading `base` against `quote`
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct OrderBookInfo<AccountId> {
+	pub owner: AccountId,
+	pub base: AssetSymbol,
+	pub quote: AssetSymbol,
+}
+
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct Order<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	pub side: OrderSide,
+	/// quote asset per unit of base asset
+	pub price: FixedU128,
+	/// base asset left to fill
+	pub amount: Balance,
+	/// funds still reserved for the order, base asset for asks and quote asset for bids
+	pub reserved: Balance,
+	pub placed_at: BlockNumber,
+	/// native asset reserved while the order rests
+	pub deposit: Balance,
+}
+
+/// A fill against a resting order, as (order id, base amount, quote amount)
+pub type Fill = (OrderId, Balance, Balance);
+
+/// A resting order in the queue of its side, with its price so the queue is walked without reading the orders
+pub type QueueEntry = (FixedU128, OrderId);
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+    pub trait Config:
+        frame_system::Config + pallet_fungible_asset::Config
+    {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Keeps a single account from taking up a whole side of the books
+		#[pallet::constant]
+		type MaxOrdersPerAccount: Get<u32>;
+
+		/// Native asset reserved for each resting order, given back when it is filled or cancelled
+		#[pallet::constant]
+		type OrderDeposit: Get<Balance>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		// two walks of the other side reading up to `MAX_FILLS_PER_CALL` orders each, the fills, then the resting part
+		#[pallet::weight(10_000_000 + 4 * QUEUE_WEIGHT + T::DbWeight::get().reads_writes(
+			10 + 8 * MAX_FILLS_PER_CALL as Weight,
+			8 + 5 * MAX_FILLS_PER_CALL as Weight,
+		))]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			side: OrderSide,
+			price: FixedU128,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::place_limit_order(
+				&who,
+				&pid,
+				side,
+				price,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + QUEUE_WEIGHT + T::DbWeight::get().reads_writes(5, 5))]
+		pub fn cancel_order(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			order_id: OrderId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::cancel_limit_order(
+				&who,
+				&pid,
+				order_id,
+			)?;
+			Ok(().into())
+		}
+	}
+
+	#[pallet::storage]
+    #[pallet::getter(fn order_books)]
+    pub type OrderBooks<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		OrderBookInfo<T::AccountId>,
+        OptionQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn orders)]
+    pub type Orders<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        PoolId,
+        Twox64Concat,
+        OrderId,
+		Order<T::AccountId, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+	/// resting orders of each side, best price first and the oldest first at the same price
+	#[pallet::storage]
+    #[pallet::getter(fn order_queues)]
+    pub type OrderQueues<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        PoolId,
+        Twox64Concat,
+        OrderSide,
+		Vec<QueueEntry>,
+        ValueQuery,
+    >;
+
+	/// resting orders of each account over all the books
+	#[pallet::storage]
+    #[pallet::getter(fn account_orders_count)]
+    pub type AccountOrdersCount<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+		u32,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn next_order_id)]
+    pub type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+	#[pallet::storage]
+    #[pallet::getter(fn book_volumes)]
+    pub type BookVolumes<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		VolumeData,
+        ValueQuery,
+    >;
+
+	#[pallet::error]
+    pub enum Error<T> {
+		InvalidPoolId,
+		OrderBookAlreadyExists,
+		OrderBookNotExists,
+		InvalidOwner,
+		SameBaseAndQuote,
+		HasOpenOrders,
+		ZeroAmount,
+		ZeroPrice,
+		TooManyOrders,
+		TooManyAccountOrders,
+		OrderNotExists,
+		NotOrderOwner,
+		SymbolNotExistsInPool,
+		NoEnoughLiquidity,
+		NoEnoughSwapAmount,
+		NoEnoughReserved,
+		TooManyFills,
+	}
+
+	#[pallet::event]
+    #[pallet::metadata(AccountIdOf<T> = "AccountId", PoolId = "PoolId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+		/// book, owner, base and quote asset
+		OrderBookCreated(PoolId, AccountIdOf<T>, AssetSymbol, AssetSymbol),
+		OrderBookDestroyed(PoolId, AccountIdOf<T>),
+		/// the part of an order left resting after matching, with its price and base amount
+		OrderPlaced(PoolId, OrderId, AccountIdOf<T>, OrderSide, FixedU128, Balance),
+		/// resting order, its owner, the taker, then the base and quote amounts traded
+		OrderFilled(PoolId, OrderId, AccountIdOf<T>, AccountIdOf<T>, Balance, Balance),
+		OrderCancelled(PoolId, OrderId, AccountIdOf<T>),
+		SwapAsset(PoolId, AccountIdOf<T>, AssetSymbol, Balance, AssetSymbol, Balance),
+	}
+}
+
+pub use pallet::*;
+
+impl<T: Config> Pallet<T> {
+	pub fn ensure_order_book_exists(pid: &PoolId) -> DispatchResult {
+		ensure!(
+			OrderBooks::<T>::contains_key(pid),
+			Error::<T>::OrderBookNotExists
+		);
+		Ok(())
+	}
+
+	/// Creates an empty book of `base` priced in `quote`, done through pallet_pool_manager so the pool is tracked there
+	pub fn create_order_book(
+		owner: &T::AccountId,
+		pid: &PoolId,
+		base: &AssetSymbol,
+		quote: &AssetSymbol,
+	) -> DispatchResult {
+		ensure!(
+			crate::is_valid_id(pid),
+			Error::<T>::InvalidPoolId
+		);
+		ensure!(
+			!OrderBooks::<T>::contains_key(pid),
+			Error::<T>::OrderBookAlreadyExists
+		);
+		ensure!(
+			base != quote,
+			Error::<T>::SameBaseAndQuote
+		);
+		FungibleAsset::<T>::ensure_asset_exists(&FungibleAsset::<T>::get_asset_id(base))?;
+		FungibleAsset::<T>::ensure_asset_exists(&FungibleAsset::<T>::get_asset_id(quote))?;
+
+		OrderBooks::<T>::insert(pid, OrderBookInfo {
+			owner: owner.clone(),
+			base: base.clone(),
+			quote: quote.clone(),
+		});
+		Self::deposit_event(Event::OrderBookCreated(pid.clone(), owner.clone(), base.clone(), quote.clone()));
+		Ok(())
+	}
+
+	/// Only an empty book can be destroyed, the owners cancel their orders first
+	pub fn destroy_order_book(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+	) -> DispatchResult {
+		let info = OrderBooks::<T>::get(pid).ok_or(Error::<T>::OrderBookNotExists)?;
+		ensure!(
+			info.owner == *issuer,
+			Error::<T>::InvalidOwner
+		);
+		ensure!(
+			OrderQueues::<T>::get(pid, OrderSide::Bid).is_empty() && OrderQueues::<T>::get(pid, OrderSide::Ask).is_empty(),
+			Error::<T>::HasOpenOrders
+		);
+
+		OrderBooks::<T>::remove(pid);
+		OrderQueues::<T>::remove_prefix(pid);
+		BookVolumes::<T>::remove(pid);
+		Self::deposit_event(Event::OrderBookDestroyed(pid.clone(), issuer.clone()));
+		Ok(())
+	}
+
+	/// Matches the order against the other side of the book at the prices of the resting orders,
+	/// then leaves what is not filled resting at `price` with its funds reserved
+	#[transactional]
+	pub fn place_limit_order(
+		who: &T::AccountId,
+		pid: &PoolId,
+		side: OrderSide,
+		price: FixedU128,
+		amount: Balance,
+	) -> DispatchResult {
+		let info = OrderBooks::<T>::get(pid).ok_or(Error::<T>::OrderBookNotExists)?;
+		ensure!(
+			!price.is_zero(),
+			Error::<T>::ZeroPrice
+		);
+		ensure!(
+			amount > 0,
+			Error::<T>::ZeroAmount
+		);
+
+		let fills = Self::get_fills(pid, side, Some(price), amount, Balance::max_value());
+		let (base_filled, _) = Self::execute_fills(who, pid, &info, side, &fills)?;
+		let remaining = amount - base_filled;
+		if remaining == 0 {
+			return Ok(());
+		}
+		// what is left of an order stopped by the fill limit must not rest across the book
+		ensure!(
+			fills.len() < MAX_FILLS_PER_CALL || Self::get_fills(pid, side, Some(price), remaining, Balance::max_value()).is_empty(),
+			Error::<T>::TooManyFills
+		);
+
+		let mut queue = OrderQueues::<T>::get(pid, side);
+		ensure!(
+			queue.len() < MAX_ORDERS_PER_SIDE,
+			Error::<T>::TooManyOrders
+		);
+		ensure!(
+			AccountOrdersCount::<T>::get(who) < T::MaxOrdersPerAccount::get(),
+			Error::<T>::TooManyAccountOrders
+		);
+		let (reserved_asset, reserved) = match side {
+			OrderSide::Bid => (info.quote, Self::quote_for_base(price, remaining)),
+			OrderSide::Ask => (info.base, remaining),
+		};
+		ensure!(
+			reserved > 0,
+			Error::<T>::ZeroAmount
+		);
+		FungibleAsset::<T>::ensure_not_frozen(&reserved_asset, who)?;
+		T::Currency::reserve(FungibleAsset::<T>::get_asset_id(&reserved_asset), who, reserved)?;
+		let deposit = T::OrderDeposit::get();
+		if deposit > 0 {
+			T::Currency::reserve(T::NativeAssetId::get(), who, deposit)?;
+		}
+
+		let order_id = NextOrderId::<T>::get();
+		NextOrderId::<T>::put(order_id + 1);
+		// behind every order at a better or the same price
+		let position = queue.binary_search_by(|(queued_price, _)| {
+			let is_ahead = match side {
+				OrderSide::Bid => *queued_price >= price,
+				OrderSide::Ask => *queued_price <= price,
+			};
+			if is_ahead { Ordering::Less } else { Ordering::Greater }
+		}).unwrap_or_else(|position| position);
+		queue.insert(position, (price, order_id));
+		OrderQueues::<T>::insert(pid, side, queue);
+		AccountOrdersCount::<T>::mutate(who, |count| *count += 1);
+		Orders::<T>::insert(pid, order_id, Order {
+			owner: who.clone(),
+			side,
+			price,
+			amount: remaining,
+			reserved,
+			placed_at: <frame_system::Pallet<T>>::block_number(),
+			deposit,
+		});
+		Self::deposit_event(Event::OrderPlaced(pid.clone(), order_id, who.clone(), side, price, remaining));
+		Ok(())
+	}
+
+	pub fn cancel_limit_order(
+		who: &T::AccountId,
+		pid: &PoolId,
+		order_id: OrderId,
+	) -> DispatchResult {
+		let info = OrderBooks::<T>::get(pid).ok_or(Error::<T>::OrderBookNotExists)?;
+		let order = Orders::<T>::get(pid, order_id).ok_or(Error::<T>::OrderNotExists)?;
+		ensure!(
+			order.owner == *who,
+			Error::<T>::NotOrderOwner
+		);
+
+		Self::close_order(pid, &info, order_id, &order);
+		OrderQueues::<T>::mutate(pid, order.side, |queue| queue.retain(|(_, id)| *id != order_id));
+		Self::deposit_event(Event::OrderCancelled(pid.clone(), order_id, who.clone()));
+		Ok(())
+	}
+
+	pub fn is_swappable(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		asset_out: &AssetSymbol,
+	) -> bool {
+		OrderBooks::<T>::get(pid).map_or(false, |info|
+			(info.base == *asset_in && info.quote == *asset_out)
+			|| (info.quote == *asset_in && info.base == *asset_out)
+		)
+	}
+
+	pub fn get_all_symbols(pid: &PoolId) -> Vec<AssetSymbol> {
+		OrderBooks::<T>::get(pid).map_or(Vec::new(), |info| sp_std::vec![info.base, info.quote])
+	}
+
+	/// Output of taking the resting orders with `amount_in`, part of which may be left unspent when the book runs out
+	pub fn get_swap_return_asset(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+	) -> Balance {
+		if !Self::is_swappable(pid, asset_in, asset_out) {
+			return 0;
+		}
+		let (side, fills) = Self::get_swap_fills(pid, asset_in, amount_in);
+		let (base, quote) = Self::sum_fills(&fills);
+		match side {
+			OrderSide::Bid => base,
+			OrderSide::Ask => quote,
+		}
+	}
+
+	/// Takes the resting orders with `amount_in` at their own prices, only the filled part of `amount_in` is spent
+	#[transactional]
+	pub fn swap_asset(
+		who: &T::AccountId,
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		min_amount_out: Balance,
+	) -> DispatchResult {
+		ensure!(
+			Self::is_swappable(pid, asset_in, asset_out),
+			Error::<T>::SymbolNotExistsInPool
+		);
+		ensure!(
+			amount_in > 0,
+			Error::<T>::ZeroAmount
+		);
+		let info = OrderBooks::<T>::get(pid).ok_or(Error::<T>::OrderBookNotExists)?;
+		let (side, fills) = Self::get_swap_fills(pid, asset_in, amount_in);
+		let (base, quote) = Self::sum_fills(&fills);
+		let (amount_spent, amount_out) = match side {
+			OrderSide::Bid => (quote, base),
+			OrderSide::Ask => (base, quote),
+		};
+		ensure!(
+			amount_out > 0,
+			Error::<T>::NoEnoughLiquidity
+		);
+		ensure!(
+			amount_out >= min_amount_out,
+			Error::<T>::NoEnoughSwapAmount
+		);
+
+		Self::execute_fills(who, pid, &info, side, &fills)?;
+		Self::deposit_event(Event::SwapAsset(pid.clone(), who.clone(), asset_in.clone(), amount_spent, asset_out.clone(), amount_out));
+		Ok(())
+	}
+
+	pub fn get_volume_data(pid: &PoolId) -> VolumeData {
+		BookVolumes::<T>::get(pid)
+	}
+
+	/// Quote asset for `base` at `price`, rounded down
+	pub fn quote_for_base(price: FixedU128, base: Balance) -> Balance {
+		price.saturating_mul_int(base)
+	}
+
+	/// Quote asset for `base` at `price`, rounded up
+	pub fn quote_for_base_rounded_up(price: FixedU128, base: Balance) -> Balance {
+		let quote = Self::quote_for_base(price, base);
+		if Self::base_for_quote(price, quote) < base {
+			quote.saturating_add(1)
+		}
+		else {
+			quote
+		}
+	}
+
+	/// Base asset `quote` buys at `price`, rounded down
+	pub fn base_for_quote(price: FixedU128, quote: Balance) -> Balance {
+		multiply_by_rational(quote, FixedU128::DIV, price.into_inner()).unwrap_or(Balance::max_value())
+	}
+
+	fn get_swap_fills(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+	) -> (OrderSide, Vec<Fill>) {
+		let is_buy = OrderBooks::<T>::get(pid).map_or(false, |info| info.quote == *asset_in);
+		if is_buy {
+			(OrderSide::Bid, Self::get_fills(pid, OrderSide::Bid, None, Balance::max_value(), amount_in))
+		}
+		else {
+			(OrderSide::Ask, Self::get_fills(pid, OrderSide::Ask, None, amount_in, Balance::max_value()))
+		}
+	}
+
+	/// Walks the other side of the book in price-time priority while its prices cross `limit_price`,
+	/// filling up to `max_base` of the base asset and, for a bid, spending up to `max_quote` of the quote asset.
+	/// Stops after `MAX_FILLS_PER_CALL` orders.
+	pub fn get_fills(
+		pid: &PoolId,
+		side: OrderSide,
+		limit_price: Option<FixedU128>,
+		max_base: Balance,
+		max_quote: Balance,
+	) -> Vec<Fill> {
+		let mut fills = Vec::new();
+		let mut base_left = max_base;
+		let mut quote_left = max_quote;
+		for (order_price, order_id) in OrderQueues::<T>::get(pid, side.opposite()) {
+			if fills.len() == MAX_FILLS_PER_CALL {
+				break;
+			}
+			if let Some(limit_price) = limit_price {
+				let crosses = match side {
+					OrderSide::Bid => order_price <= limit_price,
+					OrderSide::Ask => order_price >= limit_price,
+				};
+				if !crosses {
+					break;
+				}
+			}
+			let order = match Orders::<T>::get(pid, order_id) {
+				Some(order) => order,
+				None => continue,
+			};
+
+			let mut base = min(order.amount, base_left);
+			if side == OrderSide::Bid {
+				base = min(base, Self::base_for_quote(order.price, quote_left));
+			}
+			if base == 0 {
+				break;
+			}
+			// rounded against the taker, who pays the quote asset of a bid and is paid it for an ask
+			let quote = match side {
+				OrderSide::Bid => Self::quote_for_base_rounded_up(order.price, base),
+				OrderSide::Ask => Self::quote_for_base(order.price, base),
+			};
+			if quote == 0 {
+				break;
+			}
+			fills.push((order_id, base, quote));
+			base_left -= base;
+			quote_left = quote_left.saturating_sub(quote);
+		}
+		fills
+	}
+
+	fn sum_fills(fills: &[Fill]) -> (Balance, Balance) {
+		fills.iter().fold((0, 0), |(base, quote), (_, fill_base, fill_quote)| (base + fill_base, quote + fill_quote))
+	}
+
+	/// Settles the fills of `who` taking `side`, the taker pays from its free balance and is paid from the reserves
+	/// of the resting orders. Returns the base and quote amounts traded.
+	fn execute_fills(
+		who: &T::AccountId,
+		pid: &PoolId,
+		info: &OrderBookInfo<T::AccountId>,
+		side: OrderSide,
+		fills: &[Fill],
+	) -> Result<(Balance, Balance), sp_runtime::DispatchError> {
+		let mut closed = Vec::new();
+		for (order_id, base, quote) in fills {
+			let mut order = Orders::<T>::get(pid, order_id).ok_or(Error::<T>::OrderNotExists)?;
+			let (paid_asset, paid, received_asset, received) = match side {
+				OrderSide::Bid => (info.quote, *quote, info.base, *base),
+				OrderSide::Ask => (info.base, *base, info.quote, *quote),
+			};
+			ensure!(
+				order.reserved >= received,
+				Error::<T>::NoEnoughReserved
+			);
+			FungibleAsset::<T>::transfer_asset(who, &paid_asset, &order.owner, paid)?;
+			FungibleAsset::<T>::ensure_not_frozen(&received_asset, &order.owner)?;
+			T::Currency::repatriate_reserved(
+				FungibleAsset::<T>::get_asset_id(&received_asset),
+				&order.owner,
+				who,
+				received,
+				BalanceStatus::Free,
+			)?;
+
+			order.amount -= base;
+			order.reserved -= received;
+			Self::deposit_event(Event::OrderFilled(pid.clone(), *order_id, order.owner.clone(), who.clone(), *base, *quote));
+			if order.amount == 0 {
+				Self::close_order(pid, info, *order_id, &order);
+				closed.push(*order_id);
+			}
+			else {
+				Orders::<T>::insert(pid, order_id, order);
+			}
+		}
+		if closed.len() > 0 {
+			OrderQueues::<T>::mutate(pid, side.opposite(), |queue| queue.retain(|(_, id)| !closed.contains(id)));
+		}
+
+		let (base, quote) = Self::sum_fills(fills);
+		if base > 0 {
+			let (asset_in, amount_in, asset_out, amount_out) = match side {
+				OrderSide::Bid => (info.quote, quote, info.base, base),
+				OrderSide::Ask => (info.base, base, info.quote, quote),
+			};
+			BookVolumes::<T>::mutate(pid, |volume_data| {
+				volume_data.entry(asset_in).or_default().input += amount_in;
+				volume_data.entry(asset_out).or_default().output += amount_out;
+			});
+		}
+		Ok((base, quote))
+	}
+
+	/// Releases what is left reserved for the order and its deposit and removes it, its queue is left to the caller
+	fn close_order(
+		pid: &PoolId,
+		info: &OrderBookInfo<T::AccountId>,
+		order_id: OrderId,
+		order: &Order<T::AccountId, T::BlockNumber>,
+	) {
+		if order.reserved > 0 {
+			let reserved_asset = match order.side {
+				OrderSide::Bid => &info.quote,
+				OrderSide::Ask => &info.base,
+			};
+			T::Currency::unreserve(FungibleAsset::<T>::get_asset_id(reserved_asset), &order.owner, order.reserved);
+		}
+		if order.deposit > 0 {
+			T::Currency::unreserve(T::NativeAssetId::get(), &order.owner, order.deposit);
+		}
+		Orders::<T>::remove(pid, order_id);
+		AccountOrdersCount::<T>::mutate_exists(&order.owner, |count| {
+			*count = count.map(|open| open - 1).filter(|open| *open > 0);
+		});
+	}
+}
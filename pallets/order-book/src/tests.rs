@@ -0,0 +1,232 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+mod tests {
+    use crate::mock::*;
+    use crate::{Error, OrderId, OrderSide};
+    use frame_support::{assert_noop, assert_ok};
+	use frame_support::traits::Get;
+	use sp_runtime::{FixedPointNumber, FixedU128};
+
+	use base::*;
+
+	fn price(n: u128, d: u128) -> FixedU128 {
+		FixedU128::saturating_from_rational(n, d)
+	}
+
+	fn queue(side: OrderSide) -> Vec<OrderId> {
+		OrderBook::order_queues(&BOOK, side).into_iter().map(|(_, order_id)| order_id).collect()
+	}
+
+	fn create_book() {
+		assert_ok!(OrderBook::create_order_book(&ALICE, &BOOK, &TEST_SYMBOL1, &TEST_SYMBOL2));
+	}
+
+	#[test]
+	fn test_order_book() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_noop!(OrderBook::create_order_book(&ALICE, &BOOK, &TEST_SYMBOL1, &TEST_SYMBOL1),
+				Error::<Runtime>::SameBaseAndQuote);
+			assert_noop!(OrderBook::create_order_book(&ALICE, &BOOK, &TEST_SYMBOL1, &DEFAULT_SYMBOL),
+				pallet_fungible_asset::Error::<Runtime>::AssetNotExists);
+			create_book();
+			assert_noop!(OrderBook::create_order_book(&BOB, &BOOK, &TEST_SYMBOL2, &TEST_SYMBOL1),
+				Error::<Runtime>::OrderBookAlreadyExists);
+			assert_eq!(OrderBook::get_all_symbols(&BOOK), vec![TEST_SYMBOL1, TEST_SYMBOL2]);
+			assert!(OrderBook::is_swappable(&BOOK, &TEST_SYMBOL2, &TEST_SYMBOL1));
+
+			// bids reserve the quote asset at their price
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(2, 1), 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 200));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE));
+			assert_noop!(OrderBook::destroy_order_book(&ALICE, &BOOK), Error::<Runtime>::HasOpenOrders);
+			assert_noop!(OrderBook::cancel_limit_order(&ALICE, &BOOK, 0), Error::<Runtime>::NotOrderOwner);
+
+			assert_ok!(OrderBook::cancel_limit_order(&BOB, &BOOK, 0));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE));
+			assert_eq!(OrderBook::orders(&BOOK, 0), None);
+
+			// a frozen account can not move its balance into an order either
+			assert_ok!(FungibleAsset::freeze_account(&ALICE, &TEST_SYMBOL2, &BOB));
+			assert_noop!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(2, 1), 100),
+				pallet_fungible_asset::Error::<Runtime>::AccountIsFrozen);
+			assert_ok!(FungibleAsset::thaw_account(&ALICE, &TEST_SYMBOL2, &BOB));
+			assert_noop!(OrderBook::cancel_limit_order(&BOB, &BOOK, 0), Error::<Runtime>::OrderNotExists);
+
+			assert_noop!(OrderBook::destroy_order_book(&BOB, &BOOK), Error::<Runtime>::InvalidOwner);
+			assert_ok!(OrderBook::destroy_order_book(&ALICE, &BOOK));
+			assert_eq!(OrderBook::order_books(&BOOK), None);
+		});
+	}
+
+	#[test]
+	fn test_price_time_priority() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(3, 1), 100));
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(2, 1), 100));
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Ask, price(2, 1), 50));
+			assert_eq!(queue(OrderSide::Ask), vec![1, 2, 0]);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &ALICE), Ok(INITIAL_BALANCE - 200));
+
+			// fills the oldest order at the best price first, each at its own price
+			assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Bid, price(5, 2), 120));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &CAROL), Ok(INITIAL_BALANCE + 120));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &CAROL), Ok(INITIAL_BALANCE - 240));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &ALICE), Ok(INITIAL_BALANCE + 200));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &ALICE), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + 40));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 20));
+			assert_eq!(OrderBook::orders(&BOOK, 1), None);
+			assert_eq!(OrderBook::orders(&BOOK, 2).map(|order| order.amount), Some(30));
+			assert_eq!(queue(OrderSide::Ask), vec![2, 0]);
+			assert_eq!(queue(OrderSide::Bid), Vec::<u64>::new());
+
+			// the unfilled part rests at the limit price
+			assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Bid, price(3, 1), 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &CAROL), Ok(INITIAL_BALANCE - 240 - 60 - 210));
+			assert_eq!(OrderBook::orders(&BOOK, 0).map(|order| order.amount), Some(30));
+			assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Bid, price(2, 1), 50));
+			assert_eq!(queue(OrderSide::Bid), vec![3]);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &CAROL), Ok(INITIAL_BALANCE - 240 - 60 - 210 - 100));
+
+			// an ask crossing the bid takes its reserve
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(1, 1), 50));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &ALICE), Ok(INITIAL_BALANCE + 200 + 210 + 100));
+			assert_eq!(FungibleAsset::total_balance(&TEST_SYMBOL2, &CAROL), Ok(INITIAL_BALANCE - 240 - 60 - 210 - 100));
+			assert_eq!(queue(OrderSide::Bid), Vec::<u64>::new());
+
+			assert_noop!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(0, 1), 50),
+				Error::<Runtime>::ZeroPrice);
+			assert_noop!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(1, 1), 0),
+				Error::<Runtime>::ZeroAmount);
+		});
+	}
+
+	#[test]
+	fn test_swap() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			assert_noop!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, 0),
+				Error::<Runtime>::NoEnoughLiquidity);
+			assert_noop!(OrderBook::swap_asset(&BOB, &BOOK, &DEFAULT_SYMBOL, 100, &TEST_SYMBOL1, 0),
+				Error::<Runtime>::SymbolNotExistsInPool);
+
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(2, 1), 100));
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(4, 1), 100));
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Bid, price(1, 1), 100));
+
+			// buying the base asset walks up the asks
+			assert_eq!(OrderBook::get_swap_return_asset(&BOOK, &TEST_SYMBOL2, 300, &TEST_SYMBOL1), 125);
+			assert_noop!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL2, 300, &TEST_SYMBOL1, 126),
+				Error::<Runtime>::NoEnoughSwapAmount);
+			assert_ok!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL2, 300, &TEST_SYMBOL1, 125));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE + 125));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 300));
+			assert_eq!(OrderBook::orders(&BOOK, 1).map(|order| order.amount), Some(75));
+
+			// selling the base asset only spends what the bids take
+			assert_eq!(OrderBook::get_swap_return_asset(&BOOK, &TEST_SYMBOL1, 150, &TEST_SYMBOL2), 100);
+			assert_ok!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL1, 150, &TEST_SYMBOL2, 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE + 25));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 200));
+			assert_eq!(queue(OrderSide::Bid), Vec::<u64>::new());
+
+			let volume_data = OrderBook::get_volume_data(&BOOK);
+			assert_eq!(volume_data.get(&TEST_SYMBOL2).map(|volume| (volume.input, volume.output)), Some((300, 100)));
+			assert_eq!(volume_data.get(&TEST_SYMBOL1).map(|volume| (volume.input, volume.output)), Some((100, 125)));
+		});
+	}
+
+	#[test]
+	fn test_rounding_against_taker() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(1, 3), 100));
+			assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Bid, price(1, 4), 100));
+
+			// a third of the quote asset is paid as a whole unit
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(1, 3), 1));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE + 1));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 1));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &ALICE), Ok(INITIAL_BALANCE + 1));
+
+			// selling a single unit would be paid nothing
+			assert_eq!(OrderBook::get_swap_return_asset(&BOOK, &TEST_SYMBOL1, 1, &TEST_SYMBOL2), 0);
+			assert_noop!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL1, 1, &TEST_SYMBOL2, 0),
+				Error::<Runtime>::NoEnoughLiquidity);
+			assert_ok!(OrderBook::swap_asset(&BOB, &BOOK, &TEST_SYMBOL1, 4, &TEST_SYMBOL2, 1));
+		});
+	}
+
+	#[test]
+	fn test_fill_limit() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			for _ in 0..crate::MAX_FILLS_PER_CALL + 1 {
+				assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(1, 1), 1));
+			}
+
+			// the last ask would be left crossed by the resting part of the bid
+			assert_noop!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(1, 1), 60),
+				Error::<Runtime>::TooManyFills);
+			assert_eq!(OrderBook::get_swap_return_asset(&BOOK, &TEST_SYMBOL2, 60, &TEST_SYMBOL1), crate::MAX_FILLS_PER_CALL as Balance);
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(1, 1), 50));
+			assert_eq!(OrderBook::order_queues(&BOOK, OrderSide::Ask).len(), 1);
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(1, 1), 10));
+			assert_eq!(OrderBook::order_queues(&BOOK, OrderSide::Bid).len(), 1);
+		});
+	}
+
+	#[test]
+	fn test_order_deposit() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(2, 1), 100));
+			assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 10);
+			assert_eq!(Balances::reserved_balance(&BOB), 10);
+			assert_eq!(OrderBook::account_orders_count(&BOB), 1);
+			assert_ok!(OrderBook::cancel_limit_order(&BOB, &BOOK, 0));
+			assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+			assert_eq!(OrderBook::account_orders_count(&BOB), 0);
+
+			// a filled order gives its deposit back, an order filled when placed never rests or pays one
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &BOOK, OrderSide::Ask, price(1, 1), 100));
+			assert_eq!(Balances::reserved_balance(&ALICE), 10);
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Bid, price(1, 1), 100));
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert_eq!(Balances::free_balance(&ALICE), INITIAL_BALANCE);
+			assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+			assert_eq!(OrderBook::account_orders_count(&ALICE), 0);
+		});
+	}
+
+	#[test]
+	fn test_account_order_limit() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_book();
+			let max_orders = MaxOrdersPerAccount::get();
+			for _ in 0..max_orders {
+				assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Ask, price(1, 1), 1));
+			}
+			assert_noop!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Ask, price(1, 1), 1),
+				Error::<Runtime>::TooManyAccountOrders);
+			assert_eq!(Balances::reserved_balance(&CAROL), 10 * max_orders as Balance);
+
+			// other accounts still get their orders in
+			assert_ok!(OrderBook::place_limit_order(&BOB, &BOOK, OrderSide::Ask, price(1, 1), 1));
+			assert_ok!(OrderBook::cancel_limit_order(&CAROL, &BOOK, 0));
+			assert_ok!(OrderBook::place_limit_order(&CAROL, &BOOK, OrderSide::Ask, price(1, 1), 1));
+			assert_eq!(OrderBook::account_orders_count(&CAROL), max_orders);
+		});
+	}
+}
@@ -14,14 +14,25 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod stable;
+pub mod weighted;
+pub mod migration;
+
+use codec::{Decode, Encode};
 use frame_support::sp_runtime::traits::{MaybeSerializeDeserialize, Member};
-use frame_support::{ensure, Parameter};
-use frame_support::dispatch::{DispatchResult};
+use frame_support::{ensure, transactional, Parameter, RuntimeDebug};
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use frame_support::dispatch::{DispatchError, DispatchResult};
 use frame_system::ensure_signed;
-use sp_runtime::traits::Zero;
+use sp_runtime::{FixedPointNumber, FixedU128, ModuleId};
+use sp_runtime::helpers_128bit::multiply_by_rational;
+use sp_runtime::traits::{AccountIdConversion, CheckedMul, One, UniqueSaturatedInto};
 use sp_std::vec::Vec;
 use sp_std::collections::btree_map::BTreeMap;
 use sp_std::cmp::min;
+use sp_core::U256;
+use traits::MultiReservableCurrency;
 
 use alloc::string::String;
 use alloc::format;
@@ -34,6 +45,161 @@ type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
 
 pub const INIT_SHARES_SUPPLY: u128 = 1_000_000_000_000_000_000_000_000;
 pub const FEE_DIVISOR: u32 = 10_000;
+/// pool ids fit behind `SHARE_SYMBOL_PREFIX` in full, so every pool gets its own share symbol
+pub const MAX_POOL_ID_LEN: usize = FIXED_STRINGZ_SIZE - SHARE_SYMBOL_PREFIX.len();
+
+/// observations kept per pair, the oldest one is overwritten by the next
+pub const MAX_PRICE_OBSERVATIONS: usize = 64;
+/// worst case weight of a spot price, a stable pool solves its invariant over every symbol for it
+pub const SPOT_PRICE_WEIGHT: Weight = 20_000 * MAX_NUM_SYMBOLS as Weight;
+
+/// relative weight of each symbol in a weighted pool
+pub type WeightData = BTreeMap<AssetSymbol, u32>;
+
+/// Storage layout versions of the pallet
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum Releases {
+	/// reserves, volumes and the share ledger all kept in `PoolInfo`
+	V1,
+	/// reserves, volumes and shares in their own maps, shares can be wrapped into fungible assets
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// cumulative price of the first symbol of a pair in units of the second one, as `FixedU128` inner values summed per block
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq, Default)]
+pub struct PriceObservation<BlockNumber> {
+	pub block_number: BlockNumber,
+	pub price_cumulative: u128,
+}
+
+/// The latest observations of a pair, a ring buffer of at most `MAX_PRICE_OBSERVATIONS`
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq, Default)]
+pub struct PriceHistory<BlockNumber> {
+	/// index of the latest observation
+	pub last: u32,
+	pub observations: Vec<PriceObservation<BlockNumber>>,
+}
+
+impl<BlockNumber: PartialEq> PriceHistory<BlockNumber> {
+	pub fn latest(&self) -> Option<&PriceObservation<BlockNumber>> {
+		self.observations.get(self.last as usize)
+	}
+
+	pub fn find(&self, block_number: BlockNumber) -> Option<&PriceObservation<BlockNumber>> {
+		self.observations.iter().find(|observation| observation.block_number == block_number)
+	}
+
+	/// Appends `observation` and overwrites the oldest one once `MAX_PRICE_OBSERVATIONS` are kept
+	pub fn push(&mut self, observation: PriceObservation<BlockNumber>) {
+		if self.observations.len() < MAX_PRICE_OBSERVATIONS {
+			self.observations.push(observation);
+			self.last = (self.observations.len() - 1) as u32;
+		}
+		else {
+			self.last = (self.last + 1) % MAX_PRICE_OBSERVATIONS as u32;
+			self.observations[self.last as usize] = observation;
+		}
+	}
+}
+
+/// The invariant a pool keeps across swaps
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolCurve {
+	/// x * y = k over each pair of symbols
+	ConstantProduct,
+	/// StableSwap invariant over all the symbols, with the amplification coefficient
+	StableSwap(u32),
+	/// Weighted product invariant over all the symbols, with the weights kept in `PoolWeights`
+	Weighted,
+}
+
+impl Default for PoolCurve {
+	fn default() -> Self {
+		PoolCurve::ConstantProduct
+	}
+}
+
+/// Operations allowed on a pool, set by the pool owner or root
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolStatus {
+	/// everything is allowed
+	Active,
+	/// no swaps, liquidity can still be added and removed in all symbols
+	SwapsPaused,
+	/// liquidity can only be removed in all symbols
+	WithdrawOnly,
+	/// nothing is allowed
+	Frozen,
+}
+
+impl Default for PoolStatus {
+	fn default() -> Self {
+		PoolStatus::Active
+	}
+}
+
+impl PoolStatus {
+	/// Single asset joins and exits swap through the pool as well
+	pub fn can_swap(&self) -> bool {
+		*self == PoolStatus::Active
+	}
+
+	pub fn can_deposit(&self) -> bool {
+		*self == PoolStatus::Active || *self == PoolStatus::SwapsPaused
+	}
+
+	pub fn can_withdraw(&self) -> bool {
+		*self != PoolStatus::Frozen
+	}
+}
+
+/// Lets other pallets track the pools registered in this one
+pub trait PoolLifecycleHandler {
+	/// Checked before anything of a new pool is stored, fails when the pool id is taken elsewhere
+	fn ensure_can_register(pid: &PoolId) -> DispatchResult;
+	fn on_pool_registered(pid: &PoolId, curve: PoolCurve);
+	fn on_pool_unregistered(pid: &PoolId);
+}
+
+impl PoolLifecycleHandler for () {
+	fn ensure_can_register(_pid: &PoolId) -> DispatchResult { Ok(()) }
+	fn on_pool_registered(_pid: &PoolId, _curve: PoolCurve) {}
+	fn on_pool_unregistered(_pid: &PoolId) {}
+}
+
+/// Called back by a flash swap once the borrowed `amount_out` has been paid to `who`.
+/// `amount_in` of `asset_in` is taken back from `who` when the handler returns.
+pub trait FlashSwapHandler<AccountId> {
+	fn on_flash_swap(
+		who: &AccountId,
+		pid: &PoolId,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		data: &[u8],
+	) -> DispatchResult;
+}
+
+impl<AccountId> FlashSwapHandler<AccountId> for () {
+	fn on_flash_swap(
+		_who: &AccountId,
+		_pid: &PoolId,
+		_asset_out: &AssetSymbol,
+		_amount_out: Balance,
+		_asset_in: &AssetSymbol,
+		_amount_in: Balance,
+		_data: &[u8],
+	) -> DispatchResult {
+		Ok(())
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -54,6 +220,19 @@ pub mod pallet {
             + Default
 			+ From<FIXED_ARRAY>
 			+ ToFixedArray;
+
+		/// Used to derive the sovereign account of each pool, which custodies the pool reserves
+		#[pallet::constant]
+		type ModuleId: Get<ModuleId>;
+
+		/// Receives the exchange fee part of every swap when it is claimed, e.g. a treasury account
+		type ProtocolFeeRecipient: Get<Self::AccountId>;
+
+		/// Uses the assets borrowed by a flash swap before they are paid back
+		type FlashSwapHandler: FlashSwapHandler<Self::AccountId>;
+
+		/// Notified when a pool is registered or unregistered, e.g. the pool manager
+		type PoolLifecycleHandler: PoolLifecycleHandler;
 	}
 
 	#[pallet::pallet]
@@ -61,7 +240,19 @@ pub mod pallet {
     pub struct Pallet<T>(PhantomData<T>);
 
 	#[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::<T>::get() == Releases::V1 {
+				migration::migrate_to_v2::<T>()
+			}
+			else {
+				0
+			}
+		}
+	}
+
+	#[pallet::storage]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
 	#[pallet::storage]
     #[pallet::getter(fn pool_info_data)]
@@ -69,7 +260,127 @@ pub mod pallet {
         _,
         Twox64Concat,
         PoolId,
-		PoolInfo<T::AccountId>,
+		PoolInfo,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn pool_statuses)]
+    pub type PoolStatuses<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		PoolStatus,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn pool_reserves)]
+    pub type PoolReserves<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		SymbolData,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn pool_volumes)]
+    pub type PoolVolumes<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		VolumeData,
+        ValueQuery,
+    >;
+
+	/// Share balance of every liquidity provider, shares wrapped into the share asset are held by the pool account
+	#[pallet::storage]
+    #[pallet::getter(fn pool_shares)]
+    pub type PoolShares<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        PoolId,
+        Twox64Concat,
+		T::AccountId,
+		Balance,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn total_shares)]
+    pub type TotalShares<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		Balance,
+        ValueQuery,
+    >;
+
+	/// native deposit reserved for the share asset of each pool, with the account it goes back to
+	#[pallet::storage]
+    #[pallet::getter(fn share_asset_deposits)]
+    pub type ShareAssetDeposits<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		(T::AccountId, Balance),
+        OptionQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn pool_curves)]
+    pub type PoolCurves<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		PoolCurve,
+        ValueQuery,
+    >;
+
+	#[pallet::storage]
+    #[pallet::getter(fn pool_weights)]
+    pub type PoolWeights<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		WeightData,
+        ValueQuery,
+    >;
+
+	/// observed cumulative prices of each pair traded in a pool, both ways
+	#[pallet::storage]
+    #[pallet::getter(fn price_observations)]
+    pub type PriceObservations<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        PoolId,
+        Twox64Concat,
+        (AssetSymbol, AssetSymbol),
+		PriceHistory<T::BlockNumber>,
+        ValueQuery,
+    >;
+
+	/// Pools in the middle of a flash swap, which can not be traded or changed until it is paid back
+	#[pallet::storage]
+    pub(super) type FlashSwapLocks<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		bool,
+        ValueQuery,
+    >;
+
+	/// Exchange fees charged by a pool and not claimed yet, held by the pool account outside of the reserves
+	#[pallet::storage]
+    #[pallet::getter(fn protocol_fees)]
+    pub type ProtocolFees<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        PoolId,
+        Twox64Concat,
+        AssetSymbol,
+		Balance,
         ValueQuery,
     >;
 
@@ -83,6 +394,17 @@ pub mod pallet {
         ValueQuery,
     >;
 
+	/// Owners proposed for a pool, who become its owner once they accept
+	#[pallet::storage]
+    #[pallet::getter(fn pending_pool_owners)]
+    pub type PendingPoolOwners<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        PoolId,
+		T::AccountId,
+        OptionQuery,
+    >;
+
 	#[pallet::call]
     impl<T: Config> Pallet<T> {
 		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 2))]
@@ -106,7 +428,56 @@ pub mod pallet {
             Ok(().into())
 		}
 
-		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 3))]
+        pub fn register_stable(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			amplification: u32,
+			total_fee: u32,
+			exchange_fee: u32,
+			symbol_data: SymbolData,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::register_stable_pool(
+				&issuer,
+				&pid,
+				amplification,
+				total_fee,
+				exchange_fee,
+				&symbol_data,
+				description,
+			)?;
+            Ok(().into())
+		}
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 4))]
+        pub fn register_weighted(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			weights: WeightData,
+			total_fee: u32,
+			exchange_fee: u32,
+			symbol_data: SymbolData,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::register_weighted_pool(
+				&issuer,
+				&pid,
+				&weights,
+				total_fee,
+				exchange_fee,
+				&symbol_data,
+				description,
+			)?;
+            Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(
+			1 + 2 * MAX_NUM_SYMBOLS as Weight,
+			1 + 2 * MAX_NUM_SYMBOLS as Weight,
+		))]
         pub fn unregister(
 			origin: OriginFor<T>,
 			pid: PoolId,
@@ -124,13 +495,38 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			pid: PoolId,
 			amounts: SymbolData,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
 		) -> DispatchResultWithPostInfo {
 			let issuer = ensure_signed(origin)?;
-			Self::add_liquidity_to_pool(
-				&issuer,
-				&pid,
-				&amounts,
-			)?;
+			Self::with_trade_guards(&pid, deadline, max_price_impact, None, || Self::add_liquidity_to_pool(
+					&issuer,
+					&pid,
+					&amounts,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 2) + Pallet::<T>::price_observation_weight(1)
+			+ Pallet::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn add_liquidity_single_asset(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset: AssetSymbol,
+			amount: Balance,
+			min_shares: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			let pair = Self::get_paired_symbol(&pid, &asset).map(|other| (asset, other));
+			Self::with_trade_guards(&pid, deadline, max_price_impact, pair, || Self::add_single_asset_liquidity_to_pool(
+					&issuer,
+					&pid,
+					&asset,
+					amount,
+					min_shares,
+			))?;
 			Ok(().into())
 		}
 
@@ -140,18 +536,75 @@ pub mod pallet {
 			pid: PoolId,
 			shares: Balance,
 			amounts: SymbolData,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::with_trade_guards(&pid, deadline, max_price_impact, None, || Self::remove_liquidity_from_pool(
+					&issuer,
+					&pid,
+					shares,
+					&amounts,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 2)
+			+ Pallet::<T>::price_observation_weight(MAX_NUM_SYMBOLS as Weight - 1)
+			+ Pallet::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn remove_liquidity_single_asset(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			shares: Balance,
+			asset: AssetSymbol,
+			min_amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			let pair = Self::get_paired_symbol(&pid, &asset).map(|other| (other, asset));
+			Self::with_trade_guards(&pid, deadline, max_price_impact, pair, || Self::remove_single_asset_liquidity_from_pool(
+					&issuer,
+					&pid,
+					shares,
+					&asset,
+					min_amount_out,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 4))]
+		pub fn wrap_shares(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			amount: Balance,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::wrap_pool_shares(
+				&issuer,
+				&pid,
+				amount,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(3, 4))]
+		pub fn unwrap_shares(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			amount: Balance,
 		) -> DispatchResultWithPostInfo {
 			let issuer = ensure_signed(origin)?;
-			Self::remove_liquidity_from_pool(
+			Self::unwrap_pool_shares(
 				&issuer,
 				&pid,
-				shares,
-				&amounts,
+				amount,
 			)?;
 			Ok(().into())
 		}
 
-		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 3) + Pallet::<T>::price_observation_weight(1)
+			+ Pallet::<T>::trade_guard_weight(max_price_impact.is_some()))]
 		pub fn swap(
 			origin: OriginFor<T>,
 			pid: PoolId,
@@ -159,29 +612,177 @@ pub mod pallet {
 			amount_in: Balance,
 			asset_out: AssetSymbol,
 			min_amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
 		) -> DispatchResultWithPostInfo {
 			let issuer = ensure_signed(origin)?;
-			Self::swap_asset(
-				&issuer,
+			Self::with_trade_guards(&pid, deadline, max_price_impact, Some((asset_in, asset_out)), || Self::swap_asset(
+					&issuer,
+					&pid,
+					&asset_in,
+					amount_in,
+					&asset_out,
+					min_amount_out,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 3) + Pallet::<T>::price_observation_weight(1)
+			+ Pallet::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn swap_for_exact(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset_in: AssetSymbol,
+			max_amount_in: Balance,
+			asset_out: AssetSymbol,
+			amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::with_trade_guards(&pid, deadline, max_price_impact, Some((asset_in, asset_out)), || Self::swap_asset_for_exact(
+					&issuer,
+					&pid,
+					&asset_in,
+					max_amount_in,
+					&asset_out,
+					amount_out,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(5_000_000 + T::DbWeight::get().reads_writes(3, 4) + Pallet::<T>::price_observation_weight(1)
+			+ Pallet::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn flash_swap(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset_out: AssetSymbol,
+			amount_out: Balance,
+			asset_in: AssetSymbol,
+			max_amount_in: Balance,
+			data: Vec<u8>,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::with_trade_guards(&pid, deadline, max_price_impact, Some((asset_in, asset_out)), || Self::flash_swap_asset(
+					&issuer,
+					&pid,
+					&asset_out,
+					amount_out,
+					&asset_in,
+					max_amount_in,
+					&data,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn update_fees(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			total_fee: u32,
+			exchange_fee: u32,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_root_or_owner(origin, &pid)?;
+			Self::update_pool_fees(
 				&pid,
-				&asset_in,
-				amount_in,
-				&asset_out,
-				min_amount_out,
+				total_fee,
+				exchange_fee,
 			)?;
 			Ok(().into())
 		}
-	}
 
-	#[pallet::event]
-    #[pallet::metadata(AccountIdOf<T> = "AccountId", PoolIdOf<T> = "PoolId")]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-		PoolRegistered(PoolIdOf<T>, AccountIdOf<T>),
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn update_description(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_root_or_owner(origin, &pid)?;
+			Self::update_pool_description(
+				&pid,
+				description,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn propose_owner(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			new_owner: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_root_or_owner(origin, &pid)?;
+			Self::propose_pool_owner(
+				&pid,
+				&new_owner,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn accept_ownership(
+			origin: OriginFor<T>,
+			pid: PoolId,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::accept_pool_ownership(
+				&issuer,
+				&pid,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn set_pool_status(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			status: PoolStatus,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_root_or_owner(origin, &pid)?;
+			Self::set_status_of_pool(
+				&pid,
+				status,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn claim_protocol_fees(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset: AssetSymbol,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_root_or_owner(origin, &pid)?;
+			Self::claim_pool_protocol_fees(
+				&pid,
+				&asset,
+			)?;
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+    #[pallet::metadata(AccountIdOf<T> = "AccountId", PoolIdOf<T> = "PoolId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+		PoolRegistered(PoolIdOf<T>, AccountIdOf<T>),
 		PoolUnregistered(PoolIdOf<T>, AccountIdOf<T>),
 		AddLiquidity(PoolIdOf<T>, AccountIdOf<T>, StdString, Balance),
 		RemoveLiquidity(PoolIdOf<T>, AccountIdOf<T>, StdString, Balance),
+		SharesWrapped(PoolIdOf<T>, AccountIdOf<T>, Balance),
+		SharesUnwrapped(PoolIdOf<T>, AccountIdOf<T>, Balance),
 		SwapAsset(PoolIdOf<T>, AccountIdOf<T>, AssetSymbol, Balance, AssetSymbol, Balance),
+		SwapAssetForExact(PoolIdOf<T>, AccountIdOf<T>, AssetSymbol, Balance, AssetSymbol, Balance),
+		ProtocolFeesClaimed(PoolIdOf<T>, AssetSymbol, Balance, AccountIdOf<T>),
+		FlashSwap(PoolIdOf<T>, AccountIdOf<T>, AssetSymbol, Balance, AssetSymbol, Balance),
+		PoolStatusChanged(PoolIdOf<T>, PoolStatus, PoolStatus),
+		/// old and new total fee, then old and new exchange fee
+		PoolFeesUpdated(PoolIdOf<T>, u32, u32, u32, u32),
+		PoolDescriptionUpdated(PoolIdOf<T>, Option<StdString>, Option<StdString>),
+		PoolOwnerProposed(PoolIdOf<T>, AccountIdOf<T>, AccountIdOf<T>),
+		PoolOwnerChanged(PoolIdOf<T>, AccountIdOf<T>, AccountIdOf<T>),
     }
 
 	#[pallet::error]
@@ -202,7 +803,27 @@ pub mod pallet {
 		NoEnoughShares,
 		TooLessSharesAmount,
 		NoEnoughSwapAmount,
+		TooLargeSwapAmount,
 		WrongInvariant,
+		InconsistentReserves,
+		InvalidAmplification,
+		TooFewSymbols,
+		InvalidWeights,
+		IncompleteLiquidity,
+		TooLargeTradeRatio,
+		UnsupportedPoolCurve,
+		ShareAssetAlreadyExists,
+		NoEnoughLiquidity,
+		NoProtocolFees,
+		HasUnclaimedProtocolFees,
+		PoolLocked,
+		FlashSwapNotRepaid,
+		DeadlinePassed,
+		TooLargePriceImpact,
+		SwapsPaused,
+		DepositsPaused,
+		PoolFrozen,
+		NotPendingOwner,
     }
 
 	#[pallet::genesis_config]
@@ -229,6 +850,7 @@ pub mod pallet {
     #[pallet::genesis_build]
     impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
         fn build(&self) {
+			StorageVersion::<T>::put(Releases::V2);
 			self.endowed_pool.iter().cloned().for_each(
                 |(account_id, pid, total_fee, exchange_fee, symbol_data, description)| {
 					let _symbol_data = symbol_data.iter().filter_map(|(symbol, value)| {
@@ -239,8 +861,9 @@ pub mod pallet {
 						}
 					).collect::<SymbolData>();
 
-					for (symbol, _) in &_symbol_data{
+					for (symbol, value) in &_symbol_data{
 						if FungibleAsset::<T>::is_asset_existed(symbol) == false {
+							// the issuer is endowed with the initial liquidity it provides to the pool
 							FungibleAsset::<T>::register_asset(
 								&account_id,
 								symbol,
@@ -249,7 +872,8 @@ pub mod pallet {
 								true,
 								true,
 								None,
-								0,
+								None,
+								*value,
 							).expect("Failed to register asset.");
 						}
 					}
@@ -276,7 +900,7 @@ pub use pallet::*;
 impl<T: Config> Pallet<T> {
 	#[inline]
 	pub fn is_valid_pool_id(pid: &PoolId) -> bool {
-		crate::is_valid_id(&pid)
+		crate::is_valid_id(&pid) && pid.0[MAX_POOL_ID_LEN..].iter().all(|byte| *byte == 0)
 	}
 
 	#[inline]
@@ -300,6 +924,339 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+	/// Passes for root and for the owner of the pool
+	pub(crate) fn ensure_root_or_owner(origin: T::Origin, pid: &PoolId) -> DispatchResult {
+		let origin: Result<frame_system::RawOrigin<T::AccountId>, T::Origin> = origin.into();
+		match origin {
+			Ok(frame_system::RawOrigin::Root) => Ok(()),
+			Ok(frame_system::RawOrigin::Signed(who)) => {
+				Self::ensure_pool_exists(pid)?;
+				ensure!(
+					Self::get_pool_owner(pid) == Some(who),
+					Error::<T>::InvalidOwner
+				);
+				Ok(())
+			},
+			_ => Err(DispatchError::BadOrigin),
+		}
+	}
+
+	pub fn update_pool_fees(
+		pid: &PoolId,
+		total_fee: u32,
+		exchange_fee: u32,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		ensure!(
+			total_fee < FEE_DIVISOR && exchange_fee <= total_fee,
+			Error::<T>::TooLargeFee
+		);
+
+		let mut pool_info = PoolInfoData::<T>::get(pid);
+		let (old_total_fee, old_exchange_fee) = (pool_info.total_fee, pool_info.exchange_fee);
+		pool_info.total_fee = total_fee;
+		pool_info.exchange_fee = exchange_fee;
+		PoolInfoData::<T>::insert(pid, pool_info);
+		Self::deposit_event(Event::PoolFeesUpdated(Self::get_pool_id(pid), old_total_fee, total_fee, old_exchange_fee, exchange_fee));
+		Ok(())
+	}
+
+	pub fn update_pool_description(
+		pid: &PoolId,
+		description: Option<StdString>,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		let old_description = PoolInfoData::<T>::mutate(pid, |pool_info| {
+			sp_std::mem::replace(&mut pool_info.description, description.clone())
+		});
+		Self::deposit_event(Event::PoolDescriptionUpdated(Self::get_pool_id(pid), old_description, description));
+		Ok(())
+	}
+
+	/// The first step of handing over a pool, replacing any owner proposed before
+	pub fn propose_pool_owner(
+		pid: &PoolId,
+		new_owner: &T::AccountId,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		PendingPoolOwners::<T>::insert(pid, new_owner.clone());
+		Self::deposit_event(Event::PoolOwnerProposed(Self::get_pool_id(pid), Self::pool_owners(pid), new_owner.clone()));
+		Ok(())
+	}
+
+	pub fn accept_pool_ownership(
+		who: &T::AccountId,
+		pid: &PoolId,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		ensure!(
+			PendingPoolOwners::<T>::get(pid).as_ref() == Some(who),
+			Error::<T>::NotPendingOwner
+		);
+
+		let old_owner = Self::pool_owners(pid);
+		PoolOwners::<T>::insert(pid, who.clone());
+		PendingPoolOwners::<T>::remove(pid);
+		Self::deposit_event(Event::PoolOwnerChanged(Self::get_pool_id(pid), old_owner, who.clone()));
+		Ok(())
+	}
+
+	pub fn set_status_of_pool(
+		pid: &PoolId,
+		status: PoolStatus,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		let old_status = PoolStatuses::<T>::get(pid);
+		PoolStatuses::<T>::insert(pid, status);
+		Self::deposit_event(Event::PoolStatusChanged(Self::get_pool_id(pid), old_status, status));
+		Ok(())
+	}
+
+	pub fn ensure_pool_unlocked(pid: &PoolId) -> DispatchResult {
+		ensure!(
+			!FlashSwapLocks::<T>::get(pid),
+			Error::<T>::PoolLocked
+		);
+		Ok(())
+	}
+
+	/// Runs `f` unless `deadline` has passed, and reverts it if the spot price of the sold symbol of `pair` in
+	/// the bought one drops more than `max_price_impact` basis points from the price before it. Proportional
+	/// joins and exits keep every price and pass no pair, they are only guarded by the deadline.
+	#[transactional]
+	pub fn with_trade_guards(
+		pid: &PoolId,
+		deadline: Option<T::BlockNumber>,
+		max_price_impact: Option<u32>,
+		pair: Option<(AssetSymbol, AssetSymbol)>,
+		f: impl FnOnce() -> DispatchResult,
+	) -> DispatchResult {
+		if let Some(deadline) = deadline {
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() <= deadline,
+				Error::<T>::DeadlinePassed
+			);
+		}
+
+		let (max_price_impact, (asset_sold, asset_bought)) = match (max_price_impact, pair) {
+			(Some(max_price_impact), Some(pair)) => (max_price_impact, pair),
+			_ => return f(),
+		};
+		let prev_price = Self::get_pair_spot_price(pid, &asset_sold, &asset_bought);
+		f()?;
+
+		if let (Some(prev_price), Some(price)) = (prev_price, Self::get_pair_spot_price(pid, &asset_sold, &asset_bought)) {
+			let limit = FixedU128::saturating_from_rational(max_price_impact, FEE_DIVISOR);
+			// a limit that can not be computed is treated as exceeded
+			ensure!(
+				price >= prev_price || prev_price.checked_mul(&limit).map_or(false, |max_drop| prev_price - price <= max_drop),
+				Error::<T>::TooLargePriceImpact
+			);
+		}
+		Ok(())
+	}
+
+	/// Weight of the price impact guard, two spot prices of the traded pair on top of the reads of the pool
+	pub fn trade_guard_weight(guarded: bool) -> Weight {
+		if guarded {
+			2 * SPOT_PRICE_WEIGHT + T::DbWeight::get().reads(7)
+		}
+		else {
+			0
+		}
+	}
+
+	/// Spot price of `asset_a` in units of `asset_b`, none unless both are funded in the pool
+	pub fn get_pair_spot_price(pid: &PoolId, asset_a: &AssetSymbol, asset_b: &AssetSymbol) -> Option<FixedU128> {
+		let symbol_data = PoolReserves::<T>::get(pid);
+		if asset_a == asset_b || *symbol_data.get(asset_a)? == 0 || *symbol_data.get(asset_b)? == 0 {
+			return None;
+		}
+		Some(Self::get_spot_price(PoolCurves::<T>::get(pid), &PoolWeights::<T>::get(pid), &symbol_data, asset_a, asset_b))
+	}
+
+	/// A funded symbol of the pool other than `asset`, the counterpart single asset joins and exits are guarded on
+	pub fn get_paired_symbol(pid: &PoolId, asset: &AssetSymbol) -> Option<AssetSymbol> {
+		PoolReserves::<T>::get(pid).into_iter()
+			.find(|(symbol, balance)| symbol != asset && *balance > 0)
+			.map(|(symbol, _)| symbol)
+	}
+
+	/// The sovereign account holding the reserves of the pool
+	pub fn pool_account_id(pid: &PoolId) -> T::AccountId {
+		T::ModuleId::get().into_sub_account(pid)
+	}
+
+	/// The fungible asset wrapping the shares of the pool, its symbol is the pool id behind `SHARE_SYMBOL_PREFIX`
+	pub fn share_asset_symbol(pid: &PoolId) -> AssetSymbol {
+		let mut symbol = FIXED_ARRAY::default();
+		let prefix_len = SHARE_SYMBOL_PREFIX.len();
+		symbol[..prefix_len].copy_from_slice(SHARE_SYMBOL_PREFIX);
+		symbol[prefix_len..].copy_from_slice(&pid.0[..MAX_POOL_ID_LEN]);
+		FixedString(symbol)
+	}
+
+	pub(crate) fn register_share_asset(pid: &PoolId) -> DispatchResult {
+		let pool_account = Self::pool_account_id(pid);
+		let share_symbol = Self::share_asset_symbol(pid);
+		if FungibleAsset::<T>::is_asset_existed(&share_symbol) {
+			// a share asset still owned by the pool account, left behind by an older unregister, is reused
+			ensure!(
+				FungibleAsset::<T>::is_asset_owner(&share_symbol, &pool_account),
+				Error::<T>::ShareAssetAlreadyExists
+			);
+			return Ok(());
+		}
+
+		FungibleAsset::<T>::register_share_asset(
+			&pool_account,
+			&share_symbol,
+			&AssetName::default(),
+			DEFAULT_BALANCE_PRECISION,
+		)
+	}
+
+	/// The share asset is owned by the pool account, so its `AssetDeposit` is reserved from the registrant of the pool
+	/// instead and released to it when the pool is unregistered
+	fn reserve_share_deposit(issuer: &T::AccountId, pid: &PoolId) -> DispatchResult {
+		let deposit = <T as pallet_fungible_asset::Config>::AssetDeposit::get();
+		if deposit > 0 {
+			<T as pallet_fungible_asset::Config>::Currency::reserve(
+				<T as pallet_fungible_asset::Config>::NativeAssetId::get(),
+				issuer,
+				deposit,
+			)?;
+			ShareAssetDeposits::<T>::insert(pid, (issuer.clone(), deposit));
+		}
+		Ok(())
+	}
+
+	pub(crate) fn mint_shares(
+		pid: &PoolId,
+		who: &T::AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		let total = TotalShares::<T>::get(pid).checked_add(amount).ok_or(Error::<T>::InvalidBalance)?;
+		Self::move_shares(pid, None, Some(who), amount)?;
+		TotalShares::<T>::insert(pid, total);
+		Ok(())
+	}
+
+	fn burn_shares(
+		pid: &PoolId,
+		who: &T::AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		Self::move_shares(pid, Some(who), None, amount)?;
+		TotalShares::<T>::mutate(pid, |total| *total -= amount);
+		Ok(())
+	}
+
+	/// Moves `amount` shares between two entries of the ledger, `None` being the side minted from or burned to
+	fn move_shares(
+		pid: &PoolId,
+		from: Option<&T::AccountId>,
+		to: Option<&T::AccountId>,
+		amount: Balance,
+	) -> DispatchResult {
+		if let Some(from) = from {
+			let balance = PoolShares::<T>::get(pid, from);
+			ensure!(
+				balance >= amount,
+				Error::<T>::NoEnoughShares
+			);
+			if balance == amount {
+				PoolShares::<T>::remove(pid, from);
+			}
+			else {
+				PoolShares::<T>::insert(pid, from, balance - amount);
+			}
+		}
+		if let Some(to) = to {
+			PoolShares::<T>::mutate(pid, to, |balance| *balance += amount);
+		}
+		Ok(())
+	}
+
+	/// Turns `amount` shares of `issuer` into the share asset of the pool, which can be moved like any other asset.
+	/// The wrapped shares stay in the ledger under the pool account until they are unwrapped.
+	#[transactional]
+	pub fn wrap_pool_shares(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		amount: Balance,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		ensure!(
+			amount > 0,
+			Error::<T>::ZeroShares
+		);
+		let pool_account = Self::pool_account_id(pid);
+		Self::move_shares(pid, Some(issuer), Some(&pool_account), amount)?;
+		FungibleAsset::<T>::mint_asset(&pool_account, &Self::share_asset_symbol(pid), amount)?;
+		FungibleAsset::<T>::transfer_asset(&pool_account, &Self::share_asset_symbol(pid), issuer, amount)?;
+		Self::deposit_event(Event::SharesWrapped(Self::get_pool_id(pid), issuer.clone(), amount));
+		Ok(())
+	}
+
+	/// Burns `amount` of the share asset held by `issuer` and credits the shares back to it in the ledger
+	#[transactional]
+	pub fn unwrap_pool_shares(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		amount: Balance,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		ensure!(
+			amount > 0,
+			Error::<T>::ZeroShares
+		);
+		let pool_account = Self::pool_account_id(pid);
+		FungibleAsset::<T>::transfer_asset(issuer, &Self::share_asset_symbol(pid), &pool_account, amount)?;
+		FungibleAsset::<T>::burn_asset(&pool_account, &Self::share_asset_symbol(pid), amount)?;
+		Self::move_shares(pid, Some(&pool_account), Some(issuer), amount)?;
+		Self::deposit_event(Event::SharesUnwrapped(Self::get_pool_id(pid), issuer.clone(), amount));
+		Ok(())
+	}
+
+	/// Ensures the pool account holds exactly the reserves plus the unclaimed protocol fees
+	pub fn ensure_reserves_consistent(pid: &PoolId) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		let pool_account = Self::pool_account_id(pid);
+
+		for (symbol, reserve) in &PoolReserves::<T>::get(pid) {
+			ensure!(
+				FungibleAsset::<T>::free_balance(symbol, &pool_account)? == *reserve + ProtocolFees::<T>::get(pid, symbol),
+				Error::<T>::InconsistentReserves
+			);
+		}
+		Ok(())
+	}
+
+	/// Unclaimed protocol fees of the pool per symbol
+	pub fn get_protocol_fees(pid: &PoolId) -> SymbolData {
+		ProtocolFees::<T>::iter_prefix(pid).collect()
+	}
+
+	/// Pays the protocol fees accumulated in `asset` out to `T::ProtocolFeeRecipient`
+	pub fn claim_pool_protocol_fees(
+		pid: &PoolId,
+		asset: &AssetSymbol,
+	) -> Result<Balance, DispatchError> {
+		Self::ensure_pool_exists(pid)?;
+		let amount = ProtocolFees::<T>::get(pid, asset);
+		ensure!(
+			amount > 0,
+			Error::<T>::NoProtocolFees
+		);
+
+		let recipient = T::ProtocolFeeRecipient::get();
+		FungibleAsset::<T>::transfer_asset(&Self::pool_account_id(pid), asset, &recipient, amount)?;
+		ProtocolFees::<T>::remove(pid, asset);
+		Self::deposit_event(Event::ProtocolFeesClaimed(Self::get_pool_id(pid), asset.clone(), amount, recipient));
+		Ok(amount)
+	}
+
 	pub fn register_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
@@ -307,6 +1264,76 @@ impl<T: Config> Pallet<T> {
 		exchange_fee: u32,
 		symbol_data: &SymbolData,
 		description: Option<StdString>,
+	) -> DispatchResult {
+		Self::do_register_pool(issuer, pid, PoolCurve::ConstantProduct, &WeightData::new(), total_fee, exchange_fee, symbol_data, description)
+	}
+
+	/// Registers a pool using the StableSwap invariant, the initial liquidity must cover every symbol
+	pub fn register_stable_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		amplification: u32,
+		total_fee: u32,
+		exchange_fee: u32,
+		symbol_data: &SymbolData,
+		description: Option<StdString>,
+	) -> DispatchResult {
+		ensure!(
+			amplification > 0 && amplification <= stable::MAX_AMPLIFICATION,
+			Error::<T>::InvalidAmplification
+		);
+		ensure!(
+			symbol_data.len() >= 2,
+			Error::<T>::TooFewSymbols
+		);
+		ensure!(
+			symbol_data.iter().all(|(_, balance)| *balance > 0),
+			Error::<T>::IncompleteLiquidity
+		);
+		Self::do_register_pool(issuer, pid, PoolCurve::StableSwap(amplification), &WeightData::new(), total_fee, exchange_fee, symbol_data, description)
+	}
+
+	/// Registers a pool using the weighted product invariant, every symbol needs a weight and some initial liquidity
+	pub fn register_weighted_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		weights: &WeightData,
+		total_fee: u32,
+		exchange_fee: u32,
+		symbol_data: &SymbolData,
+		description: Option<StdString>,
+	) -> DispatchResult {
+		ensure!(
+			symbol_data.len() >= 2,
+			Error::<T>::TooFewSymbols
+		);
+		ensure!(
+			symbol_data.len() <= weighted::MAX_WEIGHTED_SYMBOLS,
+			Error::<T>::TooManySymbols
+		);
+		ensure!(
+			weights.len() == symbol_data.len()
+			&& weights.iter().all(|(symbol, weight)| *weight > 0 && symbol_data.contains_key(symbol)),
+			Error::<T>::InvalidWeights
+		);
+		ensure!(
+			symbol_data.iter().all(|(_, balance)| *balance > 0),
+			Error::<T>::IncompleteLiquidity
+		);
+		Self::do_register_pool(issuer, pid, PoolCurve::Weighted, weights, total_fee, exchange_fee, symbol_data, description)
+	}
+
+	/// Nothing of the pool is kept when the initial liquidity can not be deposited
+	#[transactional]
+	fn do_register_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		curve: PoolCurve,
+		weights: &WeightData,
+		total_fee: u32,
+		exchange_fee: u32,
+		symbol_data: &SymbolData,
+		description: Option<StdString>,
 	) -> DispatchResult {
 		ensure!(
 			Self::is_valid_pool_id(pid),
@@ -316,6 +1343,7 @@ impl<T: Config> Pallet<T> {
 				Self::get_pool_owner(pid).is_none(),
 				Error::<T>::PoolAlreadyExists,
 		);
+		T::PoolLifecycleHandler::ensure_can_register(pid)?;
 		ensure!(
 			total_fee < FEE_DIVISOR && exchange_fee <= total_fee,
 			Error::<T>::TooLargeFee
@@ -340,20 +1368,23 @@ impl<T: Config> Pallet<T> {
 			);
 		}
 
+		Self::register_share_asset(pid)?;
+		Self::reserve_share_deposit(issuer, pid)?;
+
 		PoolOwners::<T>::insert(pid.clone(), issuer.clone());
+		PoolCurves::<T>::insert(pid.clone(), curve);
+		if weights.len() > 0 {
+			PoolWeights::<T>::insert(pid.clone(), weights.clone());
+		}
 
 		let pool_info = PoolInfo{pid: pid.clone()
 								 , total_fee: total_fee
 								 , exchange_fee: exchange_fee
-								 , shares_total_supply: 0
-								 , shares_data: Default::default()
-								 , symbol_data: symbol_data.clone()
-								 , volume_data: Default::default()
 								 , description: description
 								 };
 		PoolInfoData::<T>::insert(pid.clone(), pool_info.clone());
-
-		Self::deposit_event(Event::PoolRegistered(Self::get_pool_id(pid), issuer.clone()));
+		// reserves start empty, the initial liquidity is deposited by add_liquidity_to_pool below
+		PoolReserves::<T>::insert(pid.clone(), symbol_data.iter().map(|(symbol, _)| (*symbol, 0)).collect::<SymbolData>());
 
 		let _symbol_data = symbol_data.iter().filter_map(|(key, value)| {
 				match *value > 0 {
@@ -365,9 +1396,14 @@ impl<T: Config> Pallet<T> {
 		if _symbol_data.len() > 0 {
 			Self::add_liquidity_to_pool(issuer, pid, &_symbol_data)?;
 		}
+
+		T::PoolLifecycleHandler::on_pool_registered(pid, curve);
+		Self::deposit_event(Event::PoolRegistered(Self::get_pool_id(pid), issuer.clone()));
 		Ok(())
 	}
 	
+	/// Removes the pool once all its shares are burnt, the rounding dust left in the pool account goes to the owner
+	#[transactional]
 	pub fn unregister_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
@@ -378,18 +1414,46 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::InvalidOwner,
 		);
 
-		let pool_info = PoolInfoData::<T>::get(pid);
 		ensure!(
-			pool_info.shares_total_supply == 0,
+			Self::share_total_balance(pid) == 0,
 			Error::<T>::HasRemainingShares,
 		);
+		ensure!(
+			ProtocolFees::<T>::iter_prefix(pid).next().is_none(),
+			Error::<T>::HasUnclaimedProtocolFees,
+		);
 
+		let pool_account = Self::pool_account_id(pid);
+		for symbol in PoolReserves::<T>::get(pid).keys() {
+			let dust = FungibleAsset::<T>::free_balance(symbol, &pool_account)?;
+			if dust > 0 {
+				FungibleAsset::<T>::transfer_asset(&pool_account, symbol, issuer, dust)?;
+			}
+		}
+		FungibleAsset::<T>::destroy_asset(&pool_account, &Self::share_asset_symbol(pid))?;
+		if let Some((depositor, deposit)) = ShareAssetDeposits::<T>::take(pid) {
+			<T as pallet_fungible_asset::Config>::Currency::unreserve(
+				<T as pallet_fungible_asset::Config>::NativeAssetId::get(),
+				&depositor,
+				deposit,
+			);
+		}
 		PoolInfoData::<T>::remove(pid);
+		PoolReserves::<T>::remove(pid);
+		PoolVolumes::<T>::remove(pid);
+		TotalShares::<T>::remove(pid);
 		PoolOwners::<T>::remove(pid);
+		PendingPoolOwners::<T>::remove(pid);
+		PoolStatuses::<T>::remove(pid);
+		PoolCurves::<T>::remove(pid);
+		PoolWeights::<T>::remove(pid);
+		PriceObservations::<T>::remove_prefix(pid);
+		T::PoolLifecycleHandler::on_pool_unregistered(pid);
 		Self::deposit_event(Event::PoolUnregistered(Self::get_pool_id(pid), issuer.clone()));
 		Ok(())
 	}
 
+	#[transactional]
 	pub fn add_liquidity_to_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
@@ -401,7 +1465,12 @@ impl<T: Config> Pallet<T> {
 		);
 
 		Self::ensure_pool_exists(pid)?;
-		let mut pool_info = PoolInfoData::<T>::get(pid);
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_deposit(),
+			Error::<T>::DepositsPaused
+		);
+		let mut symbol_data = PoolReserves::<T>::get(pid);
 
 		for (symbol, balance) in amounts {
 			ensure!(
@@ -409,62 +1478,348 @@ impl<T: Config> Pallet<T> {
 				Error::<T>::SymbolNotExistsInAsset
 			);
 			ensure!(
-				*balance > 0,
-				Error::<T>::InvalidBalance
+				*balance > 0,
+				Error::<T>::InvalidBalance
+			);
+			ensure!(
+				symbol_data.contains_key(&symbol),
+				Error::<T>::SymbolNotExistsInPool
+			);
+		}
+
+		let curve = PoolCurves::<T>::get(pid);
+		let shares_total_supply = Self::share_total_balance(pid);
+		let mut deposits = amounts.clone();
+		let shares = if let PoolCurve::StableSwap(amplification) = curve {
+			Self::add_stable_reserves(pid, &mut symbol_data, shares_total_supply, amplification, amounts)?
+		}
+		else if shares_total_supply > 0 {
+			// a proportional join has to bring every reserve, otherwise the missing ones are minted for free
+			ensure!(
+				symbol_data.iter().all(|(symbol, balance)| *balance == 0 || amounts.contains_key(symbol)),
+				Error::<T>::IncompleteLiquidity
+			);
+			let mut fair_supply = u128::max_value();
+			for (symbol, balance) in amounts {
+				let self_balance = symbol_data.get(&symbol).unwrap();
+				// an emptied reserve has no price to join at
+				ensure!(
+					*self_balance > 0,
+					Error::<T>::NoEnoughLiquidity
+				);
+                fair_supply = min(
+                    fair_supply,
+                    u128::from(*balance) * u128::from(shares_total_supply) / u128::from(*self_balance),
+                );
+            }
+			for (symbol, balance) in &mut deposits {
+				let self_balance = symbol_data.get_mut(symbol).unwrap();
+				let amount = u128::from(*self_balance) * fair_supply / u128::from(shares_total_supply);
+				ensure!(
+					amount > 0,
+					Error::<T>::ZeroAmount
+				);
+				*self_balance += amount;
+				*balance = amount;
+			}
+			fair_supply
+		}
+		else {
+			ensure!(
+				curve != PoolCurve::Weighted || amounts.len() == symbol_data.len(),
+				Error::<T>::IncompleteLiquidity
+			);
+			for (symbol, balance) in amounts {
+				let self_balance = symbol_data.get_mut(symbol).unwrap();
+				*self_balance += *balance;
+			}
+			INIT_SHARES_SUPPLY
+		};
+
+		ensure!(
+			shares > 0,
+			Error::<T>::ZeroShares
+		);
+
+		let pool_account = Self::pool_account_id(pid);
+		for (symbol, balance) in &deposits {
+			FungibleAsset::<T>::transfer_asset(issuer, symbol, &pool_account, *balance)?;
+		}
+		Self::mint_shares(pid, issuer, shares)?;
+		PoolReserves::<T>::insert(pid, symbol_data);
+
+		let symbol_list = format!("{:?}",
+			amounts.iter().map(|(key, _)| format!("{}", key.to_string())).collect::<Vec<String>>());
+		Self::deposit_event(Event::AddLiquidity(Self::get_pool_id(pid), issuer.clone(), StdString::from_string(&symbol_list), shares));
+		Ok(())
+	}
+
+	/// Adds `amounts` to the reserves of a stable pool and returns the shares minted for them.
+	/// As in Curve, the part of a deposit diverging from the pool balance pays the swap fee to the pool.
+	fn add_stable_reserves(
+		pid: &PoolId,
+		symbol_data: &mut SymbolData,
+		shares_total_supply: Balance,
+		amplification: u32,
+		amounts: &SymbolData,
+	) -> Result<Balance, DispatchError> {
+		let old_balances = symbol_data.values().cloned().collect::<Vec<Balance>>();
+		for (symbol, balance) in amounts {
+			*symbol_data.get_mut(symbol).unwrap() += *balance;
+		}
+		let new_balances = symbol_data.values().cloned().collect::<Vec<Balance>>();
+
+		// the first deposit has to cover every symbol, its shares are the invariant itself
+		if shares_total_supply == 0 {
+			ensure!(
+				new_balances.iter().all(|balance| *balance > 0),
+				Error::<T>::IncompleteLiquidity
+			);
+		}
+		let new_d = stable::calc_d(&new_balances, amplification).ok_or(Error::<T>::WrongInvariant)?;
+		if shares_total_supply == 0 {
+			return Ok(new_d);
+		}
+
+		let old_d = stable::calc_d(&old_balances, amplification).ok_or(Error::<T>::WrongInvariant)?;
+		ensure!(
+			new_d > old_d,
+			Error::<T>::ZeroShares
+		);
+
+		// the imbalance is traded against the other reserves, so it pays the fees of a swap
+		let pool_info = PoolInfoData::<T>::get(pid);
+		let n = new_balances.len() as u128;
+		let fee = u128::from(pool_info.total_fee) * n / (4 * (n - 1));
+		let exchange_fee = u128::from(pool_info.exchange_fee) * n / (4 * (n - 1));
+		let mut adjusted_balances = Vec::with_capacity(new_balances.len());
+		let mut protocol_fees = Vec::with_capacity(new_balances.len());
+		for (old_balance, new_balance) in old_balances.iter().zip(new_balances.iter()) {
+			let ideal_balance = multiply_by_rational(new_d, *old_balance, old_d).map_err(|_| Error::<T>::WrongInvariant)?;
+			let difference = if ideal_balance > *new_balance { ideal_balance - new_balance } else { new_balance - ideal_balance };
+			adjusted_balances.push(new_balance.saturating_sub(difference * fee / u128::from(FEE_DIVISOR)));
+			protocol_fees.push(difference * exchange_fee / u128::from(FEE_DIVISOR));
+		}
+		let adjusted_d = stable::calc_d(&adjusted_balances, amplification).ok_or(Error::<T>::WrongInvariant)?;
+		ensure!(
+			adjusted_d > old_d,
+			Error::<T>::ZeroShares
+		);
+
+		let shares = multiply_by_rational(shares_total_supply, adjusted_d - old_d, old_d)
+			.map_err(|_| Error::<T>::WrongInvariant)?;
+		let symbols = symbol_data.keys().cloned().collect::<Vec<AssetSymbol>>();
+		for (symbol, protocol_fee) in symbols.iter().zip(protocol_fees) {
+			Self::book_protocol_fee(pid, symbol_data, symbol, protocol_fee)?;
+		}
+		Ok(shares)
+	}
+
+	/// Joins a pool with a single symbol. Weighted pools mint the shares of the invariant growth, stable pools
+	/// charge the fee on the imbalance and constant product pairs first swap the optimal part into the other symbol.
+	#[transactional]
+	pub fn add_single_asset_liquidity_to_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		asset: &AssetSymbol,
+		amount: Balance,
+		min_shares: Balance,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_swap(),
+			Error::<T>::SwapsPaused
+		);
+		ensure!(
+			amount > 0,
+			Error::<T>::ZeroAmount
+		);
+
+		let mut symbol_data = PoolReserves::<T>::get(pid);
+		let total_fee = Self::get_total_fee(pid);
+		let shares_total_supply = Self::share_total_balance(pid);
+		let balance_in = *symbol_data.get(asset).ok_or(Error::<T>::SymbolNotExistsInPool)?;
+		ensure!(
+			shares_total_supply > 0 && balance_in > 0,
+			Error::<T>::IncompleteLiquidity
+		);
+		FungibleAsset::<T>::ensure_can_withdraw(asset, issuer, amount)?;
+
+		let (deposit, shares) = match PoolCurves::<T>::get(pid) {
+			PoolCurve::Weighted => {
+				ensure!(
+					amount <= balance_in / weighted::MAX_IN_RATIO,
+					Error::<T>::TooLargeTradeRatio
+				);
+				let weights = PoolWeights::<T>::get(pid);
+				let total_weight: u32 = weights.values().sum();
+				let shares = weighted::calc_shares_given_single_in(
+					balance_in,
+					get_weight(&weights, asset),
+					total_weight,
+					shares_total_supply,
+					amount,
+					Self::get_fee_rate(total_fee),
+				).ok_or(Error::<T>::WrongInvariant)?;
+				*symbol_data.get_mut(asset).unwrap() += amount;
+				// the part of the input standing for the other symbols is a swap and pays its exchange fee
+				let swapped = multiply_by_rational(amount, u128::from(total_weight - get_weight(&weights, asset)), u128::from(total_weight))
+					.map_err(|_| Error::<T>::WrongInvariant)?;
+				Self::book_protocol_fee(pid, &mut symbol_data, asset, Self::get_exchange_fee(pid, swapped))?;
+				(amount, shares)
+			},
+			PoolCurve::StableSwap(amplification) => {
+				let mut amounts = SymbolData::new();
+				amounts.insert(*asset, amount);
+				let shares = Self::add_stable_reserves(pid, &mut symbol_data, shares_total_supply, amplification, &amounts)?;
+				(amount, shares)
+			},
+			PoolCurve::ConstantProduct => {
+				Self::add_constant_product_single_asset(pid, &mut symbol_data, total_fee, shares_total_supply, asset, amount)?
+			},
+		};
+		ensure!(
+			shares > 0,
+			Error::<T>::ZeroShares
+		);
+		ensure!(
+			shares >= min_shares,
+			Error::<T>::TooLessSharesAmount
+		);
+
+		FungibleAsset::<T>::transfer_asset(issuer, asset, &Self::pool_account_id(pid), deposit)?;
+		Self::mint_shares(pid, issuer, shares)?;
+		PoolReserves::<T>::insert(pid, symbol_data);
+
+		let symbol_list = format!("{:?}", [asset.to_string()]);
+		Self::deposit_event(Event::AddLiquidity(Self::get_pool_id(pid), issuer.clone(), StdString::from_string(&symbol_list), shares));
+		Ok(())
+	}
+
+	/// Swaps the part of `amount` that balances the pair, then joins with the rest and the swap output.
+	/// Returns the amount actually taken and the shares, the few units left by rounding stay in the pool.
+	fn add_constant_product_single_asset(
+		pid: &PoolId,
+		symbol_data: &mut SymbolData,
+		total_fee: u32,
+		shares_total_supply: Balance,
+		asset: &AssetSymbol,
+		amount: Balance,
+	) -> Result<(Balance, Balance), DispatchError> {
+		let funded_symbols = symbol_data.iter()
+			.filter(|(_, balance)| **balance > 0)
+			.map(|(symbol, _)| *symbol)
+			.collect::<Vec<AssetSymbol>>();
+		// the optimal swap is only defined for a pair
+		ensure!(
+			funded_symbols.len() == 2,
+			Error::<T>::UnsupportedPoolCurve
+		);
+		let other = if funded_symbols[0] == *asset { funded_symbols[1] } else { funded_symbols[0] };
+
+		// s = (sqrt(B^2 * (2 - f)^2 + 4 * (1 - f) * A * B) - B * (2 - f)) / (2 * (1 - f))
+		let balance_in = U256::from(*symbol_data.get(asset).unwrap());
+		let fee_divisor = U256::from(FEE_DIVISOR);
+		let one_minus_fee = U256::from(FEE_DIVISOR - total_fee);
+		let two_minus_fee = U256::from(2 * FEE_DIVISOR - total_fee);
+		let root = (balance_in * balance_in * two_minus_fee * two_minus_fee
+			+ U256::from(4) * one_minus_fee * fee_divisor * U256::from(amount) * balance_in).integer_sqrt();
+		let swap_amount = ((root - balance_in * two_minus_fee) / (U256::from(2) * one_minus_fee)).low_u128();
+
+		let amount_out = Self::calc_swap_return(pid, symbol_data, total_fee, asset, swap_amount, &other);
+		ensure!(
+			swap_amount < amount && amount_out > 0,
+			Error::<T>::ZeroAmount
+		);
+		Self::apply_swap(pid, symbol_data, asset, swap_amount, &other, amount_out)?;
+
+		let balance_in = *symbol_data.get(asset).unwrap();
+		let balance_other = *symbol_data.get(&other).unwrap();
+		let shares = min(
+			multiply_by_rational(amount - swap_amount, shares_total_supply, balance_in).map_err(|_| Error::<T>::WrongInvariant)?,
+			multiply_by_rational(amount_out, shares_total_supply, balance_other).map_err(|_| Error::<T>::WrongInvariant)?,
+		);
+		let deposit = multiply_by_rational(balance_in, shares, shares_total_supply).map_err(|_| Error::<T>::WrongInvariant)?;
+		*symbol_data.get_mut(asset).unwrap() += deposit;
+		*symbol_data.get_mut(&other).unwrap() += amount_out;
+		Ok((swap_amount + deposit, shares))
+	}
+
+	/// Burns `shares` for their pro rata reserves and swaps all but `asset` into it through the curve of the pool
+	#[transactional]
+	pub fn remove_single_asset_liquidity_from_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		shares: Balance,
+		asset: &AssetSymbol,
+		min_amount_out: Balance,
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_swap(),
+			Error::<T>::SwapsPaused
+		);
+		let mut symbol_data = PoolReserves::<T>::get(pid);
+		ensure!(
+			symbol_data.contains_key(asset),
+			Error::<T>::SymbolNotExistsInPool
+		);
+
+		let shares_total_supply = Self::share_total_balance(pid);
+		ensure!(
+			shares > 0 && Self::share_balance_of(issuer, pid) >= shares && shares_total_supply >= shares,
+			Error::<T>::NoEnoughShares
+		);
+		// the last shares have nothing left to swap against
+		ensure!(
+			shares < shares_total_supply,
+			Error::<T>::NoEnoughLiquidity
+		);
+
+		let mut withdrawals = SymbolData::new();
+		for (symbol, self_balance) in symbol_data.iter_mut() {
+			let amount = u128::from(*self_balance) * u128::from(shares) / u128::from(shares_total_supply);
+			*self_balance -= amount;
+			withdrawals.insert(*symbol, amount);
+		}
+
+		// the withdrawn parts of the other symbols are sold for `asset` like any swap, with its limits and fees
+		let total_fee = Self::get_total_fee(pid);
+		let is_weighted = PoolCurves::<T>::get(pid) == PoolCurve::Weighted;
+		let mut amount_out = *withdrawals.get(asset).unwrap();
+		for (symbol, amount) in &withdrawals {
+			if symbol == asset || *amount == 0 {
+				continue;
+			}
+			ensure!(
+				!is_weighted || *amount <= *symbol_data.get(symbol).unwrap() / weighted::MAX_IN_RATIO,
+				Error::<T>::TooLargeTradeRatio
 			);
+			let swap_out = Self::calc_swap_return(pid, &symbol_data, total_fee, symbol, *amount, asset);
 			ensure!(
-				pool_info.symbol_data.contains_key(&symbol),
-				Error::<T>::SymbolNotExistsInPool
+				!is_weighted || swap_out <= *symbol_data.get(asset).unwrap() / weighted::MAX_OUT_RATIO,
+				Error::<T>::TooLargeTradeRatio
 			);
+			Self::apply_swap(pid, &mut symbol_data, symbol, *amount, asset, swap_out)?;
+			amount_out += swap_out;
 		}
-
-		let shares = if pool_info.shares_total_supply > 0 {
-			let mut fair_supply = u128::max_value();
-			for (symbol, balance) in amounts {
-				let self_balance = pool_info.symbol_data.get(&symbol).unwrap();
-                fair_supply = min(
-                    fair_supply,
-                    u128::from(*balance) * u128::from(pool_info.shares_total_supply) / u128::from(*self_balance),
-                );
-            }
-			for (symbol, balance) in &mut amounts.clone() {
-				let self_balance = pool_info.symbol_data.get_mut(symbol).unwrap();
-				let amount = u128::from(*self_balance) * fair_supply / u128::from(pool_info.shares_total_supply);
-				ensure!(
-					amount > 0,
-					Error::<T>::ZeroAmount
-				);
-				*self_balance += amount;
-				*balance = amount;
-			}
-			fair_supply
-		}
-		else {
-			for (symbol, balance) in amounts {
-				let self_balance = pool_info.symbol_data.get_mut(symbol).unwrap();
-				*self_balance += *balance;
-			}
-			INIT_SHARES_SUPPLY
-		};
-
 		ensure!(
-			shares > 0,
-			Error::<T>::ZeroShares
+			amount_out >= min_amount_out,
+			Error::<T>::NoEnoughSwapAmount
 		);
-		pool_info.shares_total_supply += shares;
-		pool_info.shares_data.insert(issuer.clone(), shares);
 
-		for (symbol, balance) in amounts {
-			FungibleAsset::<T>::update_balance(symbol, issuer, *balance as Amount)?;
-		}
-		PoolInfoData::<T>::insert(pid, pool_info.clone());
+		Self::burn_shares(pid, issuer, shares)?;
+		FungibleAsset::<T>::transfer_asset(&Self::pool_account_id(pid), asset, issuer, amount_out)?;
+		PoolReserves::<T>::insert(pid, symbol_data);
 
-		let symbol_list = format!("{:?}",
-			amounts.iter().map(|(key, _)| format!("{}", key.to_string())).collect::<Vec<String>>());
-		Self::deposit_event(Event::AddLiquidity(Self::get_pool_id(pid), issuer.clone(), StdString::from_string(&symbol_list), shares));
+		let symbol_list = format!("{:?}", [asset.to_string()]);
+		Self::deposit_event(Event::RemoveLiquidity(Self::get_pool_id(pid), issuer.clone(), StdString::from_string(&symbol_list), shares));
 		Ok(())
 	}
 
+	#[transactional]
 	pub fn remove_liquidity_from_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
@@ -472,42 +1827,68 @@ impl<T: Config> Pallet<T> {
 		amounts: &SymbolData,
 	) -> DispatchResult {
 		Self::ensure_pool_exists(pid)?;
-		let mut pool_info = PoolInfoData::<T>::get(pid);
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_withdraw(),
+			Error::<T>::PoolFrozen
+		);
+		let mut symbol_data = PoolReserves::<T>::get(pid);
 
-		if pool_info.shares_data.contains_key(&issuer) == false {
-			pool_info.shares_data.insert(issuer.clone(), Balance::zero());
-		}
-		let prev_shares_amount = pool_info.shares_data.get_mut(issuer).unwrap();
-		
+		let shares_total_supply = Self::share_total_balance(pid);
 		ensure!(
-			shares > 0 && *prev_shares_amount >= shares && pool_info.shares_total_supply >= shares,
+			shares > 0 && Self::share_balance_of(issuer, pid) >= shares && shares_total_supply >= shares,
 			Error::<T>::NoEnoughShares
 		);
 
-		for (symbol, balance) in amounts {
-			let self_balance = pool_info.symbol_data.get_mut(symbol).unwrap();
-			let amount = u128::from(*self_balance) * u128::from(shares) / u128::from(pool_info.shares_total_supply);
+		for (symbol, _) in amounts {
+			ensure!(
+				symbol_data.contains_key(symbol),
+				Error::<T>::SymbolNotExistsInPool
+			);
+		}
+
+		// every reserve is paid out pro rata, `amounts` holds the minimum expected per symbol
+		let mut withdrawals = SymbolData::new();
+		for (symbol, self_balance) in symbol_data.iter_mut() {
+			let amount = u128::from(*self_balance) * u128::from(shares) / u128::from(shares_total_supply);
 			ensure!(
-				amount >= *balance,
+				amount >= *amounts.get(symbol).unwrap_or(&0),
 				Error::<T>::TooLessSharesAmount
 			);
 			*self_balance -= amount;
+			withdrawals.insert(*symbol, amount);
 		}
 
-		if *prev_shares_amount == shares {
-            pool_info.shares_data.remove(issuer);
-        } else {
-            *prev_shares_amount -= shares;
-        }
-		pool_info.shares_total_supply -= shares;
-		PoolInfoData::<T>::insert(pid, pool_info.clone());
+		Self::burn_shares(pid, issuer, shares)?;
+		let pool_account = Self::pool_account_id(pid);
+		for (symbol, balance) in &withdrawals {
+			if *balance > 0 {
+				FungibleAsset::<T>::transfer_asset(&pool_account, symbol, issuer, *balance)?;
+			}
+		}
+		PoolReserves::<T>::insert(pid, symbol_data);
 
 		let symbol_list = format!("{:?}",
-			amounts.iter().map(|(key, _)| format!("{}", key.to_string())).collect::<Vec<String>>());
+			withdrawals.iter().map(|(key, _)| format!("{}", key.to_string())).collect::<Vec<String>>());
 		Self::deposit_event(Event::RemoveLiquidity(Self::get_pool_id(pid), issuer.clone(), StdString::from_string(&symbol_list), shares));
 		Ok(())
 	}
 
+	pub fn is_swappable(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		asset_out: &AssetSymbol,
+	) -> bool {
+		if Self::get_pool_owner(pid).is_none() || asset_in == asset_out {
+			return false;
+		}
+		let symbol_data = PoolReserves::<T>::get(pid);
+		match (symbol_data.get(asset_in), symbol_data.get(asset_out)) {
+			(Some(in_balance), Some(out_balance)) => *in_balance > 0 && *out_balance > 0,
+			_ => false,
+		}
+	}
+
 	pub fn get_swap_return_asset(
 		pid: &PoolId,
 		asset_in: &AssetSymbol,
@@ -520,9 +1901,10 @@ impl<T: Config> Pallet<T> {
 		);
 
 		let pool_info = PoolInfoData::<T>::get(pid);
+		let symbol_data = PoolReserves::<T>::get(pid);
 
-		let in_balance = pool_info.symbol_data.get(asset_in).unwrap();
-        let out_balance = pool_info.symbol_data.get(asset_out).unwrap();
+		let in_balance = symbol_data.get(asset_in).unwrap();
+        let out_balance = symbol_data.get(asset_out).unwrap();
 		assert!(
 			*in_balance > 0
 			&& *out_balance > 0
@@ -531,8 +1913,226 @@ impl<T: Config> Pallet<T> {
 			"Invalid swap data"
 		);
 
-		let amount_with_fee = u128::from(amount_in) * u128::from(FEE_DIVISOR - pool_info.total_fee);
-        amount_with_fee * out_balance / (u128::from(FEE_DIVISOR) * in_balance + amount_with_fee)
+		Self::calc_swap_return(pid, &symbol_data, pool_info.total_fee, asset_in, amount_in, asset_out)
+	}
+
+	/// Output of swapping `amount_in` against the given reserves with the curve of the pool, zero if it can not be computed
+	fn calc_swap_return(
+		pid: &PoolId,
+		symbol_data: &SymbolData,
+		total_fee: u32,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+	) -> Balance {
+		let in_balance = *symbol_data.get(asset_in).unwrap_or(&0);
+		let out_balance = *symbol_data.get(asset_out).unwrap_or(&0);
+		if in_balance == 0 || out_balance == 0 || amount_in == 0 || asset_in == asset_out {
+			return 0;
+		}
+
+		match PoolCurves::<T>::get(pid) {
+			PoolCurve::StableSwap(amplification) => {
+				Self::get_stable_swap_return(symbol_data, amplification, total_fee, asset_in, amount_in, asset_out)
+			},
+			PoolCurve::Weighted => {
+				let weights = PoolWeights::<T>::get(pid);
+				weighted::calc_out_given_in(
+					in_balance,
+					get_weight(&weights, asset_in),
+					out_balance,
+					get_weight(&weights, asset_out),
+					amount_in,
+					Self::get_fee_rate(total_fee),
+				).unwrap_or(0)
+			},
+			PoolCurve::ConstantProduct => {
+				let amount_with_fee = u128::from(amount_in) * u128::from(FEE_DIVISOR - total_fee);
+				amount_with_fee * out_balance / (u128::from(FEE_DIVISOR) * in_balance + amount_with_fee)
+			},
+		}
+	}
+
+	/// The inverse of get_swap_return_asset: the input needed to receive exactly `amount_out`, rounded up.
+	/// Fails with `TooLargeSwapAmount` when that input can not be computed or does not fit in a balance.
+	pub fn get_swap_input_for_output(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_out: Balance,
+		asset_out: &AssetSymbol,
+	) -> Result<Balance, DispatchError> {
+		Self::ensure_pool_exists(pid)?;
+		let pool_info = PoolInfoData::<T>::get(pid);
+		let symbol_data = PoolReserves::<T>::get(pid);
+
+		let in_balance = *symbol_data.get(asset_in).ok_or(Error::<T>::SymbolNotExistsInPool)?;
+		let out_balance = *symbol_data.get(asset_out).ok_or(Error::<T>::SymbolNotExistsInPool)?;
+		ensure!(
+			in_balance > 0 && asset_in != asset_out,
+			Error::<T>::NoEnoughLiquidity
+		);
+		ensure!(
+			amount_out > 0 && out_balance > amount_out,
+			Error::<T>::NoEnoughSwapAmount
+		);
+
+		let amount_in = match PoolCurves::<T>::get(pid) {
+			PoolCurve::StableSwap(amplification) => {
+				Self::get_stable_swap_input(&symbol_data, amplification, pool_info.total_fee, asset_in, amount_out, asset_out)
+			},
+			PoolCurve::Weighted => {
+				let weights = PoolWeights::<T>::get(pid);
+				weighted::calc_in_given_out(
+					in_balance,
+					get_weight(&weights, asset_in),
+					out_balance,
+					get_weight(&weights, asset_out),
+					amount_out,
+					Self::get_fee_rate(pool_info.total_fee),
+				)
+			},
+			PoolCurve::ConstantProduct => {
+				U256::from(in_balance).checked_mul(U256::from(amount_out))
+					.and_then(|product| product.checked_mul(U256::from(FEE_DIVISOR)))
+					.and_then(|numerator| numerator.checked_div(
+						U256::from(out_balance - amount_out) * U256::from(FEE_DIVISOR - pool_info.total_fee)
+					))
+					.and_then(|quotient| quotient.checked_add(U256::one()))
+					.filter(|amount_in| *amount_in <= U256::from(Balance::max_value()))
+					.map(|amount_in| amount_in.low_u128())
+			},
+		};
+		amount_in.ok_or(Error::<T>::TooLargeSwapAmount.into())
+	}
+
+	/// StableSwap output with the fee taken from the input, zero if the invariant can not be solved
+	fn get_stable_swap_return(
+		symbol_data: &SymbolData,
+		amplification: u32,
+		total_fee: u32,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+	) -> Balance {
+		let balances = symbol_data.values().cloned().collect::<Vec<Balance>>();
+		let (i, j) = match (symbol_index(symbol_data, asset_in), symbol_index(symbol_data, asset_out)) {
+			(Some(i), Some(j)) => (i, j),
+			_ => return 0,
+		};
+
+		// the fee stays in the reserves without moving the price, so the invariant grows whatever part of it is
+		// taken out as protocol fee
+		let amount_with_fee = amount_in - amount_in * u128::from(total_fee) / u128::from(FEE_DIVISOR);
+		let y = stable::calc_d(&balances, amplification)
+			.and_then(|d| stable::calc_y(&balances, i, j, balances[i].saturating_add(amount_with_fee), amplification, d));
+		match y {
+			Some(y) if y < balances[j] => balances[j] - y - 1,
+			_ => 0,
+		}
+	}
+
+	/// StableSwap input needed for `amount_out`, none if it can not be provided
+	fn get_stable_swap_input(
+		symbol_data: &SymbolData,
+		amplification: u32,
+		total_fee: u32,
+		asset_in: &AssetSymbol,
+		amount_out: Balance,
+		asset_out: &AssetSymbol,
+	) -> Option<Balance> {
+		let balances = symbol_data.values().cloned().collect::<Vec<Balance>>();
+		let i = symbol_index(symbol_data, asset_in)?;
+		let j = symbol_index(symbol_data, asset_out)?;
+
+		let dy = amount_out.checked_add(1)?;
+		if dy >= balances[j] {
+			return None;
+		}
+		let x = stable::calc_d(&balances, amplification)
+			.and_then(|d| stable::calc_y(&balances, j, i, balances[j] - dy, amplification, d))?;
+		if x <= balances[i] {
+			return None;
+		}
+		// the input before the fee, rounded up
+		let fee_rest = u128::from(FEE_DIVISOR - total_fee);
+		(x - balances[i]).checked_add(1)?
+			.checked_mul(u128::from(FEE_DIVISOR))?
+			.checked_add(fee_rest - 1)
+			.map(|amount| amount / fee_rest)
+	}
+
+	#[inline]
+	fn get_fee_rate(fee: u32) -> FixedU128 {
+		FixedU128::saturating_from_rational(fee, FEE_DIVISOR)
+	}
+
+	/// The invariant before and after a swap moved the reserves from `prev_symbol_data` to `new_symbol_data`.
+	/// Weighted pools only compare the growth of the invariant, scaled as a `FixedU128` from 1.
+	fn calc_invariants(
+		pid: &PoolId,
+		curve: PoolCurve,
+		prev_symbol_data: &SymbolData,
+		new_symbol_data: &SymbolData,
+		asset_in: &AssetSymbol,
+		asset_out: &AssetSymbol,
+	) -> Option<(u128, u128)> {
+		match curve {
+			PoolCurve::ConstantProduct => {
+				let invariant = |symbol_data: &SymbolData| {
+					integer_sqrt(*symbol_data.get(asset_in).unwrap()) * integer_sqrt(*symbol_data.get(asset_out).unwrap())
+				};
+				Some((invariant(prev_symbol_data), invariant(new_symbol_data)))
+			},
+			PoolCurve::StableSwap(amplification) => {
+				let prev_invariant = stable::calc_d(&prev_symbol_data.values().cloned().collect::<Vec<Balance>>(), amplification)?;
+				let new_invariant = stable::calc_d(&new_symbol_data.values().cloned().collect::<Vec<Balance>>(), amplification)?;
+				Some((prev_invariant, new_invariant))
+			},
+			PoolCurve::Weighted => {
+				let weights = PoolWeights::<T>::get(pid);
+				let total_weight: u32 = weights.values().sum();
+				let in_ratio = weighted::calc_invariant_ratio(
+					*prev_symbol_data.get(asset_in)?,
+					*new_symbol_data.get(asset_in)?,
+					get_weight(&weights, asset_in),
+					total_weight,
+				)?;
+				let out_ratio = weighted::calc_invariant_ratio(
+					*prev_symbol_data.get(asset_out)?,
+					*new_symbol_data.get(asset_out)?,
+					get_weight(&weights, asset_out),
+					total_weight,
+				)?;
+				Some((FixedU128::one().into_inner(), in_ratio.checked_mul(&out_ratio)?.into_inner()))
+			},
+		}
+	}
+
+	/// Marginal price of `asset_a` in units of `asset_b`
+	fn get_spot_price(
+		curve: PoolCurve,
+		weights: &WeightData,
+		symbol_data: &SymbolData,
+		asset_a: &AssetSymbol,
+		asset_b: &AssetSymbol,
+	) -> FixedU128 {
+		let balance_a = *symbol_data.get(asset_a).unwrap_or(&0);
+		let balance_b = *symbol_data.get(asset_b).unwrap_or(&0);
+		match curve {
+			PoolCurve::ConstantProduct => FixedU128::saturating_from_rational(balance_b, balance_a),
+			PoolCurve::Weighted => {
+				FixedU128::saturating_from_rational(
+					balance_b.saturating_mul(u128::from(get_weight(weights, asset_a))),
+					balance_a.saturating_mul(u128::from(get_weight(weights, asset_b))),
+				)
+			},
+			PoolCurve::StableSwap(amplification) => {
+				// quoted with a trade small enough not to move the price, without fee
+				let amount_a = (balance_a / 1_000_000).max(1);
+				let amount_b = Self::get_stable_swap_return(symbol_data, amplification, 0, asset_a, amount_a, asset_b);
+				FixedU128::saturating_from_rational(amount_b, amount_a)
+			},
+		}
 	}
 
 	pub fn swap_asset(
@@ -543,71 +2143,329 @@ impl<T: Config> Pallet<T> {
 		asset_out: &AssetSymbol,
 		min_amount_out: Balance,
 	) -> DispatchResult {
+		ensure!(
+			Self::is_swappable(pid, asset_in, asset_out),
+			Error::<T>::SymbolNotExistsInPool
+		);
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_swap(),
+			Error::<T>::SwapsPaused
+		);
+		ensure!(
+			amount_in > 0,
+			Error::<T>::ZeroAmount
+		);
+		ensure!(
+			PoolCurves::<T>::get(pid) != PoolCurve::Weighted
+			|| amount_in <= Self::get_symbol_data(pid).get(asset_in).map_or(0, |in_balance| *in_balance / weighted::MAX_IN_RATIO),
+			Error::<T>::TooLargeTradeRatio
+		);
 		let amount_out = Self::get_swap_return_asset(pid, asset_in, amount_in, asset_out);
 		ensure!(
 			amount_out >= min_amount_out,
 			Error::<T>::NoEnoughSwapAmount
 		);
 
-		FungibleAsset::<T>::ensure_can_withdraw(asset_in, who, amount_in)?;
+		Self::do_swap(who, pid, asset_in, amount_in, asset_out, amount_out)?;
+		Self::deposit_event(Event::SwapAsset(Self::get_pool_id(pid), who.clone(), asset_in.clone(), amount_in, asset_out.clone(), amount_out));
+		Ok(())
+	}
+
+	pub fn swap_asset_for_exact(
+		who: &T::AccountId,
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		max_amount_in: Balance,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+	) -> DispatchResult {
+		ensure!(
+			Self::is_swappable(pid, asset_in, asset_out),
+			Error::<T>::SymbolNotExistsInPool
+		);
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_swap(),
+			Error::<T>::SwapsPaused
+		);
+		ensure!(
+			amount_out > 0 && Self::get_symbol_data(pid).get(asset_out).map_or(false, |out_balance| *out_balance > amount_out),
+			Error::<T>::NoEnoughSwapAmount
+		);
+		ensure!(
+			PoolCurves::<T>::get(pid) != PoolCurve::Weighted
+			|| amount_out <= Self::get_symbol_data(pid).get(asset_out).map_or(0, |out_balance| *out_balance / weighted::MAX_OUT_RATIO),
+			Error::<T>::TooLargeTradeRatio
+		);
+		let amount_in = Self::get_swap_input_for_output(pid, asset_in, amount_out, asset_out)?;
+		ensure!(
+			amount_in <= max_amount_in,
+			Error::<T>::TooLargeSwapAmount
+		);
+
+		Self::do_swap(who, pid, asset_in, amount_in, asset_out, amount_out)?;
+		Self::deposit_event(Event::SwapAssetForExact(Self::get_pool_id(pid), who.clone(), asset_in.clone(), amount_in, asset_out.clone(), amount_out));
+		Ok(())
+	}
+
+	/// Pays `amount_out` to `who` before anything is paid for it, the handler then has to leave `who` with the
+	/// input of an exact output swap, or `amount_out` plus the total fee when borrowing and repaying the same symbol
+	#[transactional]
+	pub fn flash_swap_asset(
+		who: &T::AccountId,
+		pid: &PoolId,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+		asset_in: &AssetSymbol,
+		max_amount_in: Balance,
+		data: &[u8],
+	) -> DispatchResult {
+		Self::ensure_pool_exists(pid)?;
+		Self::ensure_pool_unlocked(pid)?;
+		ensure!(
+			PoolStatuses::<T>::get(pid).can_swap(),
+			Error::<T>::SwapsPaused
+		);
+		let symbol_data = PoolReserves::<T>::get(pid);
+		ensure!(
+			symbol_data.contains_key(asset_in),
+			Error::<T>::SymbolNotExistsInPool
+		);
+		ensure!(
+			amount_out > 0 && symbol_data.get(asset_out).map_or(false, |out_balance| *out_balance > amount_out),
+			Error::<T>::NoEnoughSwapAmount
+		);
 
-		let mut pool_info = PoolInfoData::<T>::get(&pid);
-		let prev_invariant: u128;
-		{
-			let in_balance = pool_info.symbol_data.get(asset_in).unwrap();
-			let out_balance = pool_info.symbol_data.get(asset_out).unwrap();
-			prev_invariant = integer_sqrt(*in_balance) * integer_sqrt(*out_balance);
+		let amount_in = if asset_in == asset_out {
+			// a plain loan pays the total fee on the borrowed amount, rounded up
+			let total_fee = u128::from(PoolInfoData::<T>::get(pid).total_fee);
+			amount_out + (amount_out * total_fee + u128::from(FEE_DIVISOR) - 1) / u128::from(FEE_DIVISOR)
 		}
+		else {
+			ensure!(
+				Self::is_swappable(pid, asset_in, asset_out),
+				Error::<T>::SymbolNotExistsInPool
+			);
+			ensure!(
+				PoolCurves::<T>::get(pid) != PoolCurve::Weighted
+				|| amount_out <= symbol_data.get(asset_out).map_or(0, |out_balance| *out_balance / weighted::MAX_OUT_RATIO),
+				Error::<T>::TooLargeTradeRatio
+			);
+			Self::get_swap_input_for_output(pid, asset_in, amount_out, asset_out)?
+		};
+		ensure!(
+			amount_in <= max_amount_in,
+			Error::<T>::TooLargeSwapAmount
+		);
+
+		let pool_account = Self::pool_account_id(pid);
+		FungibleAsset::<T>::transfer_asset(&pool_account, asset_out, who, amount_out)?;
+
+		FlashSwapLocks::<T>::insert(pid, true);
+		let result = T::FlashSwapHandler::on_flash_swap(who, pid, asset_out, amount_out, asset_in, amount_in, data);
+		FlashSwapLocks::<T>::remove(pid);
+		result?;
+
+		FungibleAsset::<T>::transfer_asset(who, asset_in, &pool_account, amount_in)
+			.map_err(|_| Error::<T>::FlashSwapNotRepaid)?;
+		Self::update_reserves_after_swap(pid, asset_in, amount_in, asset_out, amount_out)?;
+
+		Self::deposit_event(Event::FlashSwap(Self::get_pool_id(pid), who.clone(), asset_out.clone(), amount_out, asset_in.clone(), amount_in));
+		Ok(())
+	}
+
+	#[transactional]
+	fn do_swap(
+		who: &T::AccountId,
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+	) -> DispatchResult {
+		FungibleAsset::<T>::ensure_can_withdraw(asset_in, who, amount_in)?;
+		Self::update_reserves_after_swap(pid, asset_in, amount_in, asset_out, amount_out)?;
+
+		let pool_account = Self::pool_account_id(pid);
+		FungibleAsset::<T>::transfer_asset(who, asset_in, &pool_account, amount_in)?;
+		FungibleAsset::<T>::transfer_asset(&pool_account, asset_out, who, amount_out)
+	}
+
+	/// Books a swap paid into and out of the pool account: the reserves move and keep the invariant of the curve,
+	/// the exchange fee part of the input goes to the protocol fees
+	fn update_reserves_after_swap(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+	) -> DispatchResult {
+		let mut symbol_data = PoolReserves::<T>::get(pid);
+		Self::apply_swap(pid, &mut symbol_data, asset_in, amount_in, asset_out, amount_out)?;
+		PoolReserves::<T>::insert(pid, symbol_data);
+		Ok(())
+	}
 
-		let in_balance: u128;
-		let out_balance: u128;
-		{
-			let in_balance_mut = pool_info.symbol_data.get_mut(asset_in).unwrap();
-			*in_balance_mut += amount_in;
-			in_balance = *in_balance_mut;
+	/// Moves `symbol_data` by a swap and keeps the invariant of the curve, the exchange fee part of the input is
+	/// booked as protocol fee. Swaps and the swap legs of single asset joins and exits all go through it.
+	fn apply_swap(
+		pid: &PoolId,
+		symbol_data: &mut SymbolData,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+	) -> DispatchResult {
+		if asset_in != asset_out {
+			Self::update_price_cumulative(pid, symbol_data, asset_in, asset_out);
 		}
-		{
-			let out_balance_mut = pool_info.symbol_data.get_mut(asset_out).unwrap();
-			*out_balance_mut += amount_out;
-			out_balance = *out_balance_mut;
+		let pool_info = PoolInfoData::<T>::get(pid);
+		// a loan of the same symbol only pays its share of the fee charged on top of the borrowed amount
+		let protocol_fee = if asset_in != asset_out {
+			Self::get_exchange_fee(pid, amount_in)
+		}
+		else if pool_info.total_fee > 0 {
+			(amount_in - amount_out) * u128::from(pool_info.exchange_fee) / u128::from(pool_info.total_fee)
 		}
-		let new_invariant = integer_sqrt(in_balance) * integer_sqrt(out_balance);
+		else {
+			0
+		};
+		let prev_symbol_data = symbol_data.clone();
+		*symbol_data.get_mut(asset_in).ok_or(Error::<T>::SymbolNotExistsInPool)? += amount_in;
+		let out_balance = symbol_data.get_mut(asset_out).ok_or(Error::<T>::SymbolNotExistsInPool)?;
+		*out_balance = out_balance.checked_sub(amount_out).ok_or(Error::<T>::NoEnoughLiquidity)?;
+		Self::book_protocol_fee(pid, symbol_data, asset_in, protocol_fee)?;
+		let (prev_invariant, new_invariant) = Self::calc_invariants(
+			pid,
+			PoolCurves::<T>::get(pid),
+			&prev_symbol_data,
+			&symbol_data,
+			asset_in,
+			asset_out,
+		).ok_or(Error::<T>::WrongInvariant)?;
 		ensure!(
 			new_invariant >= prev_invariant,
 			Error::<T>::WrongInvariant
 		);
 
-		let numerator = (new_invariant - prev_invariant) * u128::from(pool_info.shares_total_supply);
-		if pool_info.exchange_fee > 0 && numerator > 0 {
-            let denominator = new_invariant * u128::from(pool_info.total_fee) / u128::from(pool_info.exchange_fee);
-			let shares = numerator / denominator;
-			pool_info.shares_total_supply += shares;
-            pool_info.shares_data.insert(who.clone(), shares);
-        }
+		PoolVolumes::<T>::mutate(pid, |volume_data| {
+			volume_data.entry(*asset_in).or_default().input += amount_in;
+			volume_data.entry(*asset_out).or_default().output += amount_out;
+		});
+		Ok(())
+	}
+
+	/// The exchange fee part of `amount` traded through the pool, it goes to the protocol instead of the reserves
+	fn get_exchange_fee(pid: &PoolId, amount: Balance) -> Balance {
+		multiply_by_rational(amount, u128::from(PoolInfoData::<T>::get(pid).exchange_fee), u128::from(FEE_DIVISOR))
+			.unwrap_or(0)
+	}
+
+	/// Takes `fee` out of the reserve of `asset` for the protocol, the pool account keeps holding it until claimed
+	fn book_protocol_fee(
+		pid: &PoolId,
+		symbol_data: &mut SymbolData,
+		asset: &AssetSymbol,
+		fee: Balance,
+	) -> DispatchResult {
+		if fee > 0 {
+			let balance = symbol_data.get_mut(asset).ok_or(Error::<T>::SymbolNotExistsInPool)?;
+			*balance = balance.checked_sub(fee).ok_or(Error::<T>::NoEnoughLiquidity)?;
+			ProtocolFees::<T>::mutate(pid, asset, |fees| *fees += fee);
+		}
+		Ok(())
+	}
+
+	/// Weight of observing the prices of `swaps` traded pairs, each of them both ways
+	pub fn price_observation_weight(swaps: Weight) -> Weight {
+		swaps * (2 * SPOT_PRICE_WEIGHT + T::DbWeight::get().reads_writes(4, 2))
+	}
+
+	/// The observation of the pair at `now`, accumulating its current price since `observation`
+	fn accumulate_price(
+		observation: &PriceObservation<T::BlockNumber>,
+		pid: &PoolId,
+		symbol_data: &SymbolData,
+		asset_a: &AssetSymbol,
+		asset_b: &AssetSymbol,
+		now: T::BlockNumber,
+	) -> PriceObservation<T::BlockNumber> {
+		let elapsed: u128 = (now - observation.block_number).unique_saturated_into();
+		let price = if symbol_data.get(asset_a).map_or(false, |balance| *balance > 0)
+			&& symbol_data.get(asset_b).map_or(false, |balance| *balance > 0) {
+			Self::get_spot_price(PoolCurves::<T>::get(pid), &PoolWeights::<T>::get(pid), symbol_data, asset_a, asset_b).into_inner()
+		}
+		else {
+			0
+		};
+		// overflow is intended, only differences between two observations are meaningful
+		PriceObservation{block_number: now
+						 , price_cumulative: observation.price_cumulative.wrapping_add(price.wrapping_mul(elapsed))
+						 }
+	}
 
-		FungibleAsset::<T>::update_balance(asset_in, who, -(amount_in as Amount))?;
-		FungibleAsset::<T>::update_balance(asset_out, who, amount_out as Amount)?;
+	/// Observes the price of the traded pair both ways, must be called with the reserves from before the swap
+	/// changes them. Only the first swap of the pair in a block is observed.
+	fn update_price_cumulative(
+		pid: &PoolId,
+		symbol_data: &SymbolData,
+		asset_in: &AssetSymbol,
+		asset_out: &AssetSymbol,
+	) {
+		let now = <frame_system::Pallet<T>>::block_number();
+		for (asset_a, asset_b) in &[(asset_in, asset_out), (asset_out, asset_in)] {
+			PriceObservations::<T>::mutate(pid, (**asset_a, **asset_b), |history| {
+				let observation = match history.latest() {
+					Some(latest) if latest.block_number >= now => return,
+					Some(latest) => Self::accumulate_price(latest, pid, symbol_data, asset_a, asset_b, now),
+					None => PriceObservation{block_number: now, price_cumulative: 0},
+				};
+				history.push(observation);
+			});
+		}
+	}
 
-		let sv_in: SwapVolume;
-		{
-			let mut default_sv = SwapVolume::default();
-			let sv_in_mut = pool_info.volume_data.get_mut(asset_in).unwrap_or(&mut default_sv);
-			sv_in_mut.input += amount_in;
-			sv_in = sv_in_mut.clone();
+	/// The cumulative price of `asset_a` in units of `asset_b` at the current block, extrapolated from the latest
+	/// observation of the pair with the current reserves
+	pub fn get_current_price_cumulative(
+		pid: &PoolId,
+		asset_a: &AssetSymbol,
+		asset_b: &AssetSymbol,
+	) -> Option<u128> {
+		let history = PriceObservations::<T>::get(pid, (*asset_a, *asset_b));
+		let latest = history.latest()?;
+		let now = <frame_system::Pallet<T>>::block_number();
+		if now <= latest.block_number {
+			return Some(latest.price_cumulative);
 		}
-		pool_info.volume_data.insert(asset_in.clone(), sv_in);
-		let sv_out: SwapVolume;
-		{
-			let mut default_sv = SwapVolume::default();
-			let sv_out_mut = pool_info.volume_data.get_mut(&asset_out).unwrap_or(&mut default_sv);
-			sv_out_mut.output += amount_out;
-			sv_out = sv_out_mut.clone();
+		Some(Self::accumulate_price(latest, pid, &Self::get_symbol_data(pid), asset_a, asset_b, now).price_cumulative)
+	}
+
+	/// Time weighted average price of `asset_a` in units of `asset_b` between two observations of the pair,
+	/// `end_block` may also be the current block
+	pub fn get_twap(
+		pid: &PoolId,
+		asset_a: &AssetSymbol,
+		asset_b: &AssetSymbol,
+		start_block: T::BlockNumber,
+		end_block: T::BlockNumber,
+	) -> Option<FixedU128> {
+		if start_block >= end_block {
+			return None;
 		}
-		pool_info.volume_data.insert(asset_out.clone(), sv_out);
-		PoolInfoData::<T>::insert(pid, pool_info.clone());
 
-		Self::deposit_event(Event::SwapAsset(Self::get_pool_id(pid), who.clone(), asset_in.clone(), amount_in, asset_out.clone(), amount_out));
-		Ok(())
+		let history = PriceObservations::<T>::get(pid, (*asset_a, *asset_b));
+		let start_cumulative = history.find(start_block)?.price_cumulative;
+		let end_cumulative = match history.find(end_block) {
+			Some(observation) => observation.price_cumulative,
+			None if end_block == <frame_system::Pallet<T>>::block_number() => {
+				Self::get_current_price_cumulative(pid, asset_a, asset_b)?
+			},
+			None => return None,
+		};
+		let elapsed: u128 = (end_block - start_block).unique_saturated_into();
+		Some(FixedU128::from_inner(end_cumulative.wrapping_sub(start_cumulative) / elapsed))
 	}
 
 	pub fn share_balance_of(
@@ -617,8 +2475,7 @@ impl<T: Config> Pallet<T> {
 		if Self::get_pool_owner(pid).is_none() {
 			return 0;
 		}
-		let pool_info = PoolInfoData::<T>::get(pid);
-		*pool_info.shares_data.get(who).unwrap_or(&0)
+		PoolShares::<T>::get(pid, who)
 	}
 
 	pub fn share_total_balance(
@@ -627,8 +2484,7 @@ impl<T: Config> Pallet<T> {
 		if Self::get_pool_owner(pid).is_none() {
 			return 0;
 		}
-		let pool_info = PoolInfoData::<T>::get(pid);
-		pool_info.shares_total_supply
+		TotalShares::<T>::get(pid)
 	}
 
 	pub fn get_symbol_data(
@@ -637,8 +2493,7 @@ impl<T: Config> Pallet<T> {
 		if Self::get_pool_owner(pid).is_none() {
 			return BTreeMap::new();
 		}
-		let pool_info = PoolInfoData::<T>::get(pid);
-		pool_info.symbol_data.clone()
+		PoolReserves::<T>::get(pid)
 	}
 
 	pub fn get_all_symbols(
@@ -648,8 +2503,7 @@ impl<T: Config> Pallet<T> {
 		if Self::get_pool_owner(pid).is_none() {
 			return v;
 		}
-		let pool_info = PoolInfoData::<T>::get(pid);
-		for (symbol, _) in &pool_info.symbol_data{
+		for (symbol, _) in &PoolReserves::<T>::get(pid){
 			v.push(symbol.clone());
 		}
 		v
@@ -665,14 +2519,33 @@ impl<T: Config> Pallet<T> {
 		pool_info.total_fee
 	}
 
+	pub fn get_pool_curve(
+		pid: &PoolId,
+	) -> PoolCurve {
+		PoolCurves::<T>::get(pid)
+	}
+
+	pub fn get_pool_weights(
+		pid: &PoolId,
+	) -> WeightData {
+		PoolWeights::<T>::get(pid)
+	}
+
 	pub fn get_volume_data(
 		pid: &PoolId,
 	) -> VolumeData {
 		if Self::get_pool_owner(pid).is_none() {
 			return VolumeData::default();
 		}
-		let pool_info = PoolInfoData::<T>::get(pid);
-		pool_info.volume_data.clone()
+		PoolVolumes::<T>::get(pid)
 	}
 
 }
+
+fn symbol_index(symbol_data: &SymbolData, symbol: &AssetSymbol) -> Option<usize> {
+	symbol_data.keys().position(|key| key == symbol)
+}
+
+fn get_weight(weights: &WeightData, symbol: &AssetSymbol) -> u32 {
+	*weights.get(symbol).unwrap_or(&0)
+}
@@ -0,0 +1,115 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+use super::*;
+use frame_support::storage::{with_transaction, TransactionOutcome};
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+
+/// `PoolInfo` as stored by `Releases::V1`
+#[derive(Encode, Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct PoolInfoV1<AccountId: Ord> {
+	pub pid: PoolId,
+	pub total_fee: u32,
+	pub exchange_fee: u32,
+	pub shares_total_supply: Balance,
+	pub shares_data: BTreeMap<AccountId, Balance>,
+	pub symbol_data: SymbolData,
+	pub volume_data: VolumeData,
+	pub description: Option<StdString>,
+}
+
+/// Moves the reserves, volumes and share ledger of every pool into their own maps. V1 left the liquidity
+/// with the providers, so each of them moves its part of the reserves into the pool account for its shares.
+/// Nothing is minted, the reserves become what the pool account ends up holding.
+/// Either every pool is migrated or nothing is, a failed migration leaves the storage at `Releases::V1`.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let mut reads: Weight = 1;
+	let mut writes: Weight = 0;
+	let result = with_transaction(|| {
+		match migrate_pools::<T>(&mut reads, &mut writes) {
+			Ok(()) => TransactionOutcome::Commit(Ok(())),
+			Err(e) => TransactionOutcome::Rollback(Err(e)),
+		}
+	});
+
+	match result {
+		Ok(()) => {
+			StorageVersion::<T>::put(Releases::V2);
+			writes += 1;
+		},
+		Err(e) => {
+			frame_support::debug::error!("pool-amm: migration to V2 failed and was rolled back: {:?}", e);
+		},
+	}
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
+fn migrate_pools<T: Config>(reads: &mut Weight, writes: &mut Weight) -> DispatchResult {
+	let mut legacy_pools = Vec::new();
+	PoolInfoData::<T>::translate::<PoolInfoV1<T::AccountId>, _>(|pid, old| {
+		PoolVolumes::<T>::insert(pid, old.volume_data);
+		legacy_pools.push((pid, old.shares_data, old.symbol_data));
+		Some(PoolInfo{pid: old.pid
+					 , total_fee: old.total_fee
+					 , exchange_fee: old.exchange_fee
+					 , description: old.description
+					 })
+	});
+	*reads += legacy_pools.len() as Weight;
+	*writes += 2 * legacy_pools.len() as Weight;
+
+	for (pid, shares_data, symbol_data) in legacy_pools {
+		Pallet::<T>::register_share_asset(&pid)?;
+		*reads += 2;
+		*writes += 3;
+
+		// whatever the pool account already holds counts towards its reserves, the providers owe the rest
+		let pool_account = Pallet::<T>::pool_account_id(&pid);
+		let mut reserves = SymbolData::new();
+		let mut owed = SymbolData::new();
+		for (symbol, reserve) in &symbol_data {
+			let held = FungibleAsset::<T>::free_balance(symbol, &pool_account)?;
+			reserves.insert(*symbol, held);
+			owed.insert(*symbol, reserve.saturating_sub(held));
+			*reads += 1;
+		}
+
+		let shares_total_supply: Balance = shares_data.values().sum();
+		for (who, shares) in shares_data {
+			if shares == 0 {
+				continue;
+			}
+			// a provider short of its part keeps the shares it can still pay for
+			let mut funded_shares = shares;
+			for (symbol, amount) in &owed {
+				let part = multiply_by_rational(*amount, shares, shares_total_supply).map_err(|_| Error::<T>::WrongInvariant)?;
+				let free = FungibleAsset::<T>::free_balance(symbol, &who)?;
+				if part > free {
+					funded_shares = min(funded_shares, multiply_by_rational(shares, free, part).map_err(|_| Error::<T>::WrongInvariant)?);
+				}
+				*reads += 1;
+			}
+			if funded_shares == 0 {
+				continue;
+			}
+
+			for (symbol, amount) in &owed {
+				let part = multiply_by_rational(*amount, funded_shares, shares_total_supply).map_err(|_| Error::<T>::WrongInvariant)?;
+				if part > 0 {
+					FungibleAsset::<T>::transfer_asset(&who, symbol, &pool_account, part)?;
+					*reserves.get_mut(symbol).unwrap() += part;
+					*writes += 2;
+				}
+			}
+			Pallet::<T>::mint_shares(&pid, &who, funded_shares)?;
+			*reads += 2;
+			*writes += 2;
+		}
+		PoolReserves::<T>::insert(pid, reserves);
+		*writes += 1;
+	}
+	Ok(())
+}
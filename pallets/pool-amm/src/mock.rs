@@ -9,12 +9,13 @@ use crate::{self as pallet_pool_amm};
 use currencies::BasicCurrencyAdapter;
 use frame_support::traits::GenesisBuild;
 use frame_support::weights::Weight;
+use frame_support::dispatch::DispatchResult;
 use frame_support::{construct_runtime, parameter_types};
 use frame_system;
 use sp_core::H256;
 use sp_runtime::testing::Header;
 use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
-use sp_runtime::Perbill;
+use sp_runtime::{ModuleId, Perbill};
 use sp_std::collections::btree_map::BTreeMap;
 
 use traits::parameter_type_with_key;
@@ -45,10 +46,16 @@ pub type Amount = i128;
 
 pub const ALICE: AccountId = FixedString::from_const_string("ALICE");
 pub const BOB: AccountId = FixedString::from_const_string("BOB");
+pub const TREASURY: AccountId = FixedString::from_const_string("TREASURY");
 pub const POOL_AMM: PoolId = FixedString::from_const_string("pool_amm");
+pub const POOL_STABLE: PoolId = FixedString::from_const_string("pool_stable");
+pub const POOL_WEIGHTED: PoolId = FixedString::from_const_string("pool_weighted");
 
 pub const TEST_SYMBOL1: AssetSymbol = AssetSymbol::from_const_string("DOT");
 pub const TEST_SYMBOL2: AssetSymbol = AssetSymbol::from_const_string("KSM");
+pub const TEST_SYMBOL3: AssetSymbol = AssetSymbol::from_const_string("ACA");
+
+pub const INITIAL_BALANCE: Balance = 10_000;
 
 parameter_types! {
     pub const BlockHashCount: u64 = 250;
@@ -57,7 +64,10 @@ parameter_types! {
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(80);
 
 	pub const GetBaseAssetId: AssetId = DEFAULT_SYMBOL;
+	pub storage AssetDeposit: Balance = 0;
 	pub const ExistentialDeposit: u128 = 0;
+	pub const PoolAmmModuleId: ModuleId = ModuleId(*b"pex/pool");
+	pub const ProtocolFeeRecipient: AccountId = TREASURY;
 }
 
 impl frame_system::Config for Runtime {
@@ -122,12 +132,42 @@ impl pallet_balances::Config for Runtime {
 impl crate::Config for Runtime {
 	type Event = Event;
 	type PoolId = PoolId;
+	type ModuleId = PoolAmmModuleId;
+	type ProtocolFeeRecipient = ProtocolFeeRecipient;
+	type FlashSwapHandler = TestFlashSwapHandler;
+	type PoolLifecycleHandler = ();
+}
+
+/// Pays nothing back with the data `spend` and trades the locked pool with `reenter`
+pub struct TestFlashSwapHandler;
+
+impl crate::FlashSwapHandler<AccountId> for TestFlashSwapHandler {
+	fn on_flash_swap(
+		who: &AccountId,
+		pid: &PoolId,
+		asset_out: &AssetSymbol,
+		amount_out: Balance,
+		asset_in: &AssetSymbol,
+		_amount_in: Balance,
+		data: &[u8],
+	) -> DispatchResult {
+		match data {
+			b"spend" => {
+				let balance = FungibleAsset::free_balance(asset_in, who)?;
+				FungibleAsset::transfer_asset(who, asset_in, &ALICE, balance)
+			},
+			b"reenter" => PoolAmm::swap_asset(who, pid, asset_out, amount_out, asset_in, 1),
+			_ => Ok(()),
+		}
+	}
 }
 
 impl pallet_fungible_asset::Config for Runtime {
     type Event = Event;
     type AssetId = AssetId;
     type Currency = currencies::Module<Runtime>;
+    type NativeAssetId = GetBaseAssetId;
+    type AssetDeposit = AssetDeposit;
 }
 
 pub struct ExtBuilder {
@@ -148,7 +188,13 @@ impl Default for ExtBuilder {
 		symbol_data.insert(TEST_SYMBOL2.clone(), 0);
 
         Self {
-            endowed_accounts: vec![(ALICE, DEFAULT_SYMBOL, 0)],
+            endowed_accounts: vec![
+				(ALICE, DEFAULT_SYMBOL, 0),
+				(ALICE, TEST_SYMBOL1, INITIAL_BALANCE),
+				(ALICE, TEST_SYMBOL2, INITIAL_BALANCE),
+				(BOB, TEST_SYMBOL1, INITIAL_BALANCE),
+				(BOB, TEST_SYMBOL2, INITIAL_BALANCE),
+			],
 			endowed_pool: vec![(ALICE, POOL_AMM, 30, 10, symbol_data, None)],
         }
     }
@@ -162,6 +208,7 @@ impl ExtBuilder {
             balances: self
                 .endowed_accounts
                 .iter()
+                .filter(|(_, symbol, _)| *symbol == DEFAULT_SYMBOL)
                 .map(|(acc, _, balance)| (*acc, *balance))
                 .collect(),
         }
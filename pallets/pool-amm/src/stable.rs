@@ -0,0 +1,90 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+//! StableSwap invariant of Curve: A*n^n*sum(x_i) + D = A*D*n^n + D^(n+1) / (n^n*prod(x_i))
+
+use sp_core::U256;
+
+pub const MAX_AMPLIFICATION: u32 = 1_000_000;
+const MAX_ITERATIONS: usize = 255;
+
+/// Newton's method for D, none if any balance is empty or the iteration diverges
+pub fn calc_d(balances: &[u128], amplification: u32) -> Option<u128> {
+	if balances.is_empty() || balances.iter().any(|balance| *balance == 0) {
+		return None;
+	}
+
+	let n = U256::from(balances.len());
+	let sum = balances.iter().fold(U256::zero(), |acc, balance| acc + U256::from(*balance));
+	let ann = U256::from(amplification).checked_mul(n)?;
+
+	let mut d = sum;
+	for _ in 0..MAX_ITERATIONS {
+		let mut d_p = d;
+		for balance in balances {
+			d_p = d_p.checked_mul(d)? / (U256::from(*balance).checked_mul(n)?);
+		}
+		let d_prev = d;
+		let numerator = ann.checked_mul(sum)?.checked_add(d_p.checked_mul(n)?)?.checked_mul(d)?;
+		let denominator = ann.checked_sub(U256::one())?.checked_mul(d)?
+			.checked_add(n.checked_add(U256::one())?.checked_mul(d_p)?)?;
+		d = numerator / denominator;
+
+		if (d > d_prev && d - d_prev <= U256::one()) || (d <= d_prev && d_prev - d <= U256::one()) {
+			return u128_from(d);
+		}
+	}
+	None
+}
+
+/// The new balance of coin `j` keeping D unchanged once the balance of coin `i` becomes `x`
+pub fn calc_y(balances: &[u128], i: usize, j: usize, x: u128, amplification: u32, d: u128) -> Option<u128> {
+	if i == j || i >= balances.len() || j >= balances.len() || x == 0 {
+		return None;
+	}
+
+	let n = U256::from(balances.len());
+	let d = U256::from(d);
+	let ann = U256::from(amplification).checked_mul(n)?;
+
+	let mut c = d;
+	let mut sum = U256::zero();
+	for (k, balance) in balances.iter().enumerate() {
+		let x_k = if k == i {
+			U256::from(x)
+		} else if k != j {
+			U256::from(*balance)
+		} else {
+			continue;
+		};
+		if x_k.is_zero() {
+			return None;
+		}
+		sum = sum.checked_add(x_k)?;
+		c = c.checked_mul(d)? / x_k.checked_mul(n)?;
+	}
+	c = c.checked_mul(d)? / ann.checked_mul(n)?;
+	let b = sum.checked_add(d / ann)?;
+
+	let mut y = d;
+	for _ in 0..MAX_ITERATIONS {
+		let y_prev = y;
+		let denominator = y.checked_mul(U256::from(2))?.checked_add(b)?.checked_sub(d)?;
+		y = y.checked_mul(y)?.checked_add(c)? / denominator;
+
+		if (y > y_prev && y - y_prev <= U256::one()) || (y <= y_prev && y_prev - y <= U256::one()) {
+			return u128_from(y);
+		}
+	}
+	None
+}
+
+fn u128_from(value: U256) -> Option<u128> {
+	if value > U256::from(u128::max_value()) {
+		None
+	} else {
+		Some(value.low_u128())
+	}
+}
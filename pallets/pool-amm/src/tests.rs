@@ -5,9 +5,12 @@
 
 mod tests {
     use crate::mock::*;
-    use crate::Error;
+    use crate::{Error, PoolReserves, PriceHistory, MAX_PRICE_OBSERVATIONS};
     use frame_support::{assert_noop, assert_ok};
+	use frame_support::traits::OnRuntimeUpgrade;
+	use sp_runtime::{FixedPointNumber, FixedU128};
 	use sp_std::collections::btree_map::BTreeMap;
+	use traits::{MultiCurrency, MultiReservableCurrency};
 
 	use base::*;
 
@@ -79,6 +82,56 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn test_register_pool_atomic() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pid = PoolId::from_const_string("pool_new");
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 100);
+			symbol_data.insert(TEST_SYMBOL2.clone(), INITIAL_BALANCE + 1);
+
+			// the first deposit goes through before the second one fails, nothing of the pool is left behind
+			assert_noop!(PoolAmm::register_pool(&BOB, &pid, 30, 10, &symbol_data, None),
+				pallet_fungible_asset::Error::<Runtime>::NoEnoughBalance
+			);
+			assert!(PoolAmm::get_pool_owner(&pid).is_none());
+
+			symbol_data.insert(TEST_SYMBOL2.clone(), 100);
+			assert_ok!(PoolAmm::register_pool(&BOB, &pid, 30, 10, &symbol_data, None));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&pid));
+		})
+	}
+
+	#[test]
+	fn test_reregister_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&BOB, 100, 30);
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 7, &TEST_SYMBOL1, 1));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_AMM);
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(&BOB, &POOL_AMM, shares, &SymbolData::new()));
+			assert_ok!(PoolAmm::claim_protocol_fees(Origin::root(), POOL_AMM, TEST_SYMBOL1));
+			assert_ok!(PoolAmm::claim_protocol_fees(Origin::root(), POOL_AMM, TEST_SYMBOL2));
+
+			// whatever rounding left in the pool account goes to the owner
+			let pool_account = PoolAmm::pool_account_id(&POOL_AMM);
+			let dust1 = FungibleAsset::free_balance(&TEST_SYMBOL1, &pool_account).unwrap();
+			let dust2 = FungibleAsset::free_balance(&TEST_SYMBOL2, &pool_account).unwrap();
+			assert_ok!(PoolAmm::unregister_pool(&ALICE, &POOL_AMM));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &pool_account), Ok(0));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &pool_account), Ok(0));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &ALICE), Ok(INITIAL_BALANCE + dust1));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &ALICE), Ok(INITIAL_BALANCE + dust2));
+
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 100);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 100);
+			assert_ok!(PoolAmm::register_pool(&ALICE, &POOL_AMM, 30, 10, &symbol_data, None));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
 	#[test]
 	fn test_liquidity() {
 		let mut ext = ExtBuilder::default().build();
@@ -138,12 +191,12 @@ mod tests {
 			assert!(FungibleAsset::free_balance(
 					&TEST_SYMBOL1,
 					&BOB,
-				) == Ok(100)
+				) == Ok(INITIAL_BALANCE - 100)
 			);
 			assert!(FungibleAsset::free_balance(
 					&TEST_SYMBOL2,
 					&BOB,
-				) == Ok(30)
+				) == Ok(INITIAL_BALANCE - 30)
 			);
 
 			let expected_asset_out1 = PoolAmm::get_swap_return_asset(
@@ -169,13 +222,14 @@ mod tests {
 			assert!(FungibleAsset::free_balance(
 					&TEST_SYMBOL1,
 					&BOB,
-				) == Ok(100 + expected_asset_out1)
+				) == Ok(INITIAL_BALANCE - 100 + expected_asset_out1)
 			);
 			assert!(FungibleAsset::free_balance(
 					&TEST_SYMBOL2,
 					&BOB,
-				) == Ok(30 - 10)
+				) == Ok(INITIAL_BALANCE - 30 - 10)
 			);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
 
 			let expected_asset_out2 = PoolAmm::get_swap_return_asset(
 				&POOL_AMM,
@@ -266,4 +320,798 @@ mod tests {
 			//assert!(false);
 		})
 	}
+
+	#[test]
+	fn test_swap_for_exact() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+
+			let amount_in = PoolAmm::get_swap_input_for_output(
+				&POOL_AMM,
+				&TEST_SYMBOL1,
+				100,
+				&TEST_SYMBOL2,
+			).unwrap();
+			assert!(PoolAmm::get_swap_return_asset(&POOL_AMM, &TEST_SYMBOL1, amount_in, &TEST_SYMBOL2) >= 100);
+			assert!(PoolAmm::get_swap_return_asset(&POOL_AMM, &TEST_SYMBOL1, amount_in - 1, &TEST_SYMBOL2) < 100);
+
+			assert_noop!(PoolAmm::swap_asset_for_exact(
+					&BOB,
+					&POOL_AMM,
+					&TEST_SYMBOL1,
+					amount_in - 1,
+					&TEST_SYMBOL2,
+					100,
+				),
+				Error::<Runtime>::TooLargeSwapAmount
+			);
+			assert_noop!(PoolAmm::swap_asset_for_exact(
+					&BOB,
+					&POOL_AMM,
+					&TEST_SYMBOL1,
+					INITIAL_BALANCE,
+					&TEST_SYMBOL2,
+					800,
+				),
+				Error::<Runtime>::NoEnoughSwapAmount
+			);
+
+			assert_ok!(PoolAmm::swap_asset_for_exact(
+                &BOB,
+                &POOL_AMM,
+				&TEST_SYMBOL1,
+				amount_in,
+				&TEST_SYMBOL2,
+				100,
+				)
+			);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - amount_in));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + 100));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_swap_for_exact_overflow() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+
+			let reserve = Balance::max_value() / 2;
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1, reserve);
+			symbol_data.insert(TEST_SYMBOL2, reserve);
+			PoolReserves::<Runtime>::insert(&POOL_AMM, symbol_data);
+
+			let amount_in = PoolAmm::get_swap_input_for_output(&POOL_AMM, &TEST_SYMBOL1, reserve / 2, &TEST_SYMBOL2).unwrap();
+			assert!(amount_in > reserve);
+			assert_eq!(
+				PoolAmm::get_swap_input_for_output(&POOL_AMM, &TEST_SYMBOL1, reserve - 1, &TEST_SYMBOL2),
+				Err(Error::<Runtime>::TooLargeSwapAmount.into())
+			);
+			assert_noop!(PoolAmm::swap_asset_for_exact(
+					&BOB,
+					&POOL_AMM,
+					&TEST_SYMBOL1,
+					INITIAL_BALANCE,
+					&TEST_SYMBOL2,
+					reserve - 1,
+				),
+				Error::<Runtime>::TooLargeSwapAmount
+			);
+		})
+	}
+
+	#[test]
+	fn test_twap() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let spot_price = |asset_a: &AssetSymbol, asset_b: &AssetSymbol| {
+				let symbol_data = PoolAmm::get_symbol_data(&POOL_AMM);
+				FixedU128::saturating_from_rational(*symbol_data.get(asset_b).unwrap(), *symbol_data.get(asset_a).unwrap())
+			};
+
+			// joins do not move the price, the first swap of the pair starts its history
+			System::set_block_number(1);
+			test_add_liquidity(&ALICE, 1000, 800);
+			assert_eq!(PoolAmm::price_observations(&POOL_AMM, (TEST_SYMBOL1, TEST_SYMBOL2)), PriceHistory::default());
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 100, &TEST_SYMBOL2, 1));
+			let first_price = spot_price(&TEST_SYMBOL1, &TEST_SYMBOL2);
+			let first_reverse_price = spot_price(&TEST_SYMBOL2, &TEST_SYMBOL1);
+
+			System::set_block_number(11);
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 50, &TEST_SYMBOL1, 1));
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 1, 11), Some(first_price));
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL2, &TEST_SYMBOL1, 1, 11), Some(first_reverse_price));
+			let second_price = spot_price(&TEST_SYMBOL1, &TEST_SYMBOL2);
+
+			// prices are extrapolated to the current block without a new observation
+			System::set_block_number(21);
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 11, 21), Some(second_price));
+			assert_eq!(
+				PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 1, 21),
+				Some(FixedU128::from_inner((first_price.into_inner() + second_price.into_inner()) / 2))
+			);
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 1, 20), None);
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 11, 11), None);
+		})
+	}
+
+	#[test]
+	fn test_price_history_bounded() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+			for block in 1..=(MAX_PRICE_OBSERVATIONS as u64 + 1) {
+				System::set_block_number(block);
+				assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 10, &TEST_SYMBOL2, 1));
+				// a second swap of the pair in the same block is not observed again
+				assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 5, &TEST_SYMBOL1, 1));
+			}
+
+			let history = PoolAmm::price_observations(&POOL_AMM, (TEST_SYMBOL1, TEST_SYMBOL2));
+			assert_eq!(history.observations.len(), MAX_PRICE_OBSERVATIONS);
+			assert_eq!(history.latest().unwrap().block_number, MAX_PRICE_OBSERVATIONS as u64 + 1);
+			assert_eq!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 1, 2), None);
+			assert!(PoolAmm::get_twap(&POOL_AMM, &TEST_SYMBOL1, &TEST_SYMBOL2, 2, 3).is_some());
+			assert_eq!(
+				PoolAmm::price_observations(&POOL_AMM, (TEST_SYMBOL2, TEST_SYMBOL1)).observations.len(),
+				MAX_PRICE_OBSERVATIONS
+			);
+		})
+	}
+
+	#[test]
+	fn test_pool_account_custody() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pool_account = PoolAmm::pool_account_id(&POOL_AMM);
+			assert!(pool_account != ALICE && pool_account != BOB);
+
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), INITIAL_BALANCE * 2);
+			amounts.insert(TEST_SYMBOL2.clone(), INITIAL_BALANCE * 2);
+			assert_noop!(PoolAmm::add_liquidity_to_pool(
+					&BOB,
+					&POOL_AMM,
+					&amounts,
+				),
+				pallet_fungible_asset::Error::<Runtime>::NoEnoughBalance
+			);
+
+			test_add_liquidity(&ALICE, 1000, 800);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &pool_account), Ok(1000));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &pool_account), Ok(800));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			assert_ok!(PoolAmm::swap_asset(
+                &BOB,
+                &POOL_AMM,
+				&TEST_SYMBOL1,
+				300,
+				&TEST_SYMBOL2,
+				1,
+				)
+			);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			let shares = PoolAmm::share_balance_of(&ALICE, &POOL_AMM);
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(
+                &ALICE,
+                &POOL_AMM,
+				shares,
+				&SymbolData::new(),
+				)
+			);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_stable_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 5000);
+			assert_noop!(PoolAmm::register_stable_pool(&ALICE, &POOL_STABLE, 100, 4, 0, &symbol_data, None),
+				Error::<Runtime>::TooFewSymbols
+			);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 0);
+			assert_noop!(PoolAmm::register_stable_pool(&ALICE, &POOL_STABLE, 100, 4, 0, &symbol_data, None),
+				Error::<Runtime>::IncompleteLiquidity
+			);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 5000);
+			assert_noop!(PoolAmm::register_stable_pool(&ALICE, &POOL_STABLE, 0, 4, 0, &symbol_data, None),
+				Error::<Runtime>::InvalidAmplification
+			);
+
+			assert_ok!(PoolAmm::register_stable_pool(&ALICE, &POOL_STABLE, 100, 4, 0, &symbol_data, None));
+			assert_eq!(PoolAmm::get_pool_curve(&POOL_STABLE), crate::PoolCurve::StableSwap(100));
+			assert_eq!(PoolAmm::get_pool_curve(&POOL_AMM), crate::PoolCurve::ConstantProduct);
+			assert!(PoolAmm::share_balance_of(&ALICE, &POOL_STABLE) > 0);
+
+			// close to parity, far better than the 833 a constant product pool would return
+			let amount_out = PoolAmm::get_swap_return_asset(&POOL_STABLE, &TEST_SYMBOL1, 1000, &TEST_SYMBOL2);
+			assert!(amount_out > 950 && amount_out < 1000);
+
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_STABLE, &TEST_SYMBOL1, 1000, &TEST_SYMBOL2, amount_out));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 1000));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + amount_out));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_STABLE));
+
+			let amount_in = PoolAmm::get_swap_input_for_output(&POOL_STABLE, &TEST_SYMBOL2, 500, &TEST_SYMBOL1).unwrap();
+			assert!(PoolAmm::get_swap_return_asset(&POOL_STABLE, &TEST_SYMBOL2, amount_in, &TEST_SYMBOL1) >= 500);
+			assert_ok!(PoolAmm::swap_asset_for_exact(&BOB, &POOL_STABLE, &TEST_SYMBOL2, amount_in, &TEST_SYMBOL1, 500));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_STABLE));
+
+			// a one sided deposit is charged the fee on its imbalance
+			let total_shares = PoolAmm::share_total_balance(&POOL_STABLE);
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 1000);
+			assert_ok!(PoolAmm::add_liquidity_to_pool(&BOB, &POOL_STABLE, &amounts));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_STABLE);
+			assert!(shares > 0 && shares < total_shares / 10);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_STABLE));
+
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(&BOB, &POOL_STABLE, shares, &SymbolData::new()));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_STABLE));
+		})
+	}
+
+	#[test]
+	fn test_weighted_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_ok!(FungibleAsset::register_asset(&ALICE, &TEST_SYMBOL3, &AssetName::default(), 18, true, true, None, None, INITIAL_BALANCE));
+
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 2000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 1000);
+			symbol_data.insert(TEST_SYMBOL3.clone(), 1000);
+			let mut weights: crate::WeightData = BTreeMap::new();
+			weights.insert(TEST_SYMBOL1.clone(), 2);
+			weights.insert(TEST_SYMBOL2.clone(), 1);
+			assert_noop!(PoolAmm::register_weighted_pool(&ALICE, &POOL_WEIGHTED, &weights, 30, 0, &symbol_data, None),
+				Error::<Runtime>::InvalidWeights
+			);
+			weights.insert(TEST_SYMBOL3.clone(), 1);
+			assert_ok!(PoolAmm::register_weighted_pool(&ALICE, &POOL_WEIGHTED, &weights, 30, 0, &symbol_data, None));
+			assert_eq!(PoolAmm::get_pool_curve(&POOL_WEIGHTED), crate::PoolCurve::Weighted);
+			assert_eq!(PoolAmm::get_pool_weights(&POOL_WEIGHTED), weights);
+
+			// DOT weighs twice as much, so the reserves above are priced 1:1 and not 1:2
+			let amount_out = PoolAmm::get_swap_return_asset(&POOL_WEIGHTED, &TEST_SYMBOL2, 100, &TEST_SYMBOL1);
+			assert!(amount_out > 90 && amount_out < 100);
+			assert_noop!(PoolAmm::swap_asset(&BOB, &POOL_WEIGHTED, &TEST_SYMBOL2, 501, &TEST_SYMBOL1, 1),
+				Error::<Runtime>::TooLargeTradeRatio
+			);
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_WEIGHTED, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, amount_out));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE + amount_out));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_WEIGHTED));
+
+			let amount_in = PoolAmm::get_swap_input_for_output(&POOL_WEIGHTED, &TEST_SYMBOL1, 50, &TEST_SYMBOL3).unwrap();
+			assert!(PoolAmm::get_swap_return_asset(&POOL_WEIGHTED, &TEST_SYMBOL1, amount_in, &TEST_SYMBOL3) >= 50);
+			assert_ok!(PoolAmm::swap_asset_for_exact(&BOB, &POOL_WEIGHTED, &TEST_SYMBOL1, amount_in, &TEST_SYMBOL3, 50));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL3, &BOB), Ok(50));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_WEIGHTED));
+
+			// a single sided join of about 7.5% of the DOT reserve gets a bit less than 4% of the shares
+			assert_noop!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_AMM, &TEST_SYMBOL1, 150, 0),
+				Error::<Runtime>::IncompleteLiquidity
+			);
+			let total_shares = PoolAmm::share_total_balance(&POOL_WEIGHTED);
+			assert_ok!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_WEIGHTED, &TEST_SYMBOL1, 150, 0));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_WEIGHTED);
+			assert!(shares > total_shares / 30 && shares < total_shares / 25);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_WEIGHTED));
+
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 100);
+			assert_noop!(PoolAmm::add_liquidity_to_pool(&BOB, &POOL_WEIGHTED, &amounts),
+				Error::<Runtime>::IncompleteLiquidity
+			);
+
+			// a single sided exit sells the other symbols within the ratio limits of a swap
+			let alice_shares = PoolAmm::share_balance_of(&ALICE, &POOL_WEIGHTED);
+			assert_noop!(PoolAmm::remove_single_asset_liquidity_from_pool(&ALICE, &POOL_WEIGHTED, alice_shares / 2, &TEST_SYMBOL1, 0),
+				Error::<Runtime>::TooLargeTradeRatio
+			);
+			assert_ok!(PoolAmm::remove_single_asset_liquidity_from_pool(&BOB, &POOL_WEIGHTED, shares, &TEST_SYMBOL1, 1));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_WEIGHTED));
+		})
+	}
+
+	#[test]
+	fn test_share_asset() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let share_symbol = PoolAmm::share_asset_symbol(&POOL_AMM);
+			assert_eq!(share_symbol, AssetSymbol::from_const_string("LP-pool_amm"));
+			assert!(FungibleAsset::is_asset_owner(&share_symbol, &PoolAmm::pool_account_id(&POOL_AMM)));
+
+			test_add_liquidity(&ALICE, 1000, 800);
+			let shares = PoolAmm::share_balance_of(&ALICE, &POOL_AMM);
+			assert_eq!(PoolAmm::pool_shares(&POOL_AMM, &ALICE), shares);
+			assert_eq!(FungibleAsset::total_issuance(&share_symbol), Ok(0));
+
+			// wrapped shares are plain assets, whoever unwraps them can withdraw the liquidity
+			assert_noop!(PoolAmm::wrap_pool_shares(&ALICE, &POOL_AMM, shares + 1),
+				Error::<Runtime>::NoEnoughShares
+			);
+			assert_ok!(PoolAmm::wrap_pool_shares(&ALICE, &POOL_AMM, shares / 2));
+			assert_eq!(PoolAmm::share_balance_of(&ALICE, &POOL_AMM), shares - shares / 2);
+			assert_eq!(FungibleAsset::free_balance(&share_symbol, &ALICE), Ok(shares / 2));
+			assert_eq!(PoolAmm::share_total_balance(&POOL_AMM), shares);
+
+			assert_ok!(FungibleAsset::transfer_asset(&ALICE, &share_symbol, &BOB, shares / 2));
+			assert_noop!(PoolAmm::remove_liquidity_from_pool(&ALICE, &POOL_AMM, shares, &SymbolData::new()),
+				Error::<Runtime>::NoEnoughShares
+			);
+			assert_noop!(PoolAmm::unwrap_pool_shares(&ALICE, &POOL_AMM, shares / 2),
+				pallet_fungible_asset::Error::<Runtime>::NoEnoughBalance
+			);
+			assert_ok!(PoolAmm::unwrap_pool_shares(&BOB, &POOL_AMM, shares / 2));
+			assert_eq!(PoolAmm::share_balance_of(&BOB, &POOL_AMM), shares / 2);
+			assert_eq!(FungibleAsset::total_issuance(&share_symbol), Ok(0));
+
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(&BOB, &POOL_AMM, shares / 2, &SymbolData::new()));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE + 500));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + 400));
+			assert_eq!(PoolAmm::share_total_balance(&POOL_AMM), shares - shares / 2);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_migrate_to_v2() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pid = PoolId::from_const_string("pool_legacy");
+			let dave = AccountId::from_const_string("DAVE");
+			let mut shares_data = BTreeMap::new();
+			shares_data.insert(ALICE, 600);
+			shares_data.insert(BOB, 300);
+			shares_data.insert(dave, 100);
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 1000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 800);
+			let legacy_pool_info = crate::migration::PoolInfoV1{pid: pid.clone()
+				, total_fee: 30
+				, exchange_fee: 10
+				, shares_total_supply: 1000
+				, shares_data: shares_data
+				, symbol_data: symbol_data.clone()
+				, volume_data: VolumeData::new()
+				, description: None
+				};
+			frame_support::storage::unhashed::put(&crate::PoolInfoData::<Runtime>::hashed_key_for(&pid), &legacy_pool_info);
+			crate::PoolOwners::<Runtime>::insert(&pid, ALICE);
+			crate::StorageVersion::<Runtime>::put(crate::Releases::V1);
+			let total_issuance = (Currencies::total_issuance(TEST_SYMBOL1), Currencies::total_issuance(TEST_SYMBOL2));
+
+			<PoolAmm as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(crate::StorageVersion::<Runtime>::get(), crate::Releases::V2);
+			assert_eq!(PoolAmm::pool_info_data(&pid).total_fee, 30);
+			// the providers move their parts into the pool, nothing is minted for it
+			assert_eq!((Currencies::total_issuance(TEST_SYMBOL1), Currencies::total_issuance(TEST_SYMBOL2)), total_issuance);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &ALICE), Ok(INITIAL_BALANCE - 600));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 240));
+			assert_eq!(PoolAmm::share_balance_of(&ALICE, &pid), 600);
+			assert_eq!(PoolAmm::share_balance_of(&BOB, &pid), 300);
+			// a provider without the balance for its part loses the shares it can not pay for
+			assert_eq!(PoolAmm::share_balance_of(&dave, &pid), 0);
+			assert_eq!(PoolAmm::share_total_balance(&pid), 900);
+			let mut reserves: SymbolData = BTreeMap::new();
+			reserves.insert(TEST_SYMBOL1.clone(), 900);
+			reserves.insert(TEST_SYMBOL2.clone(), 720);
+			assert_eq!(PoolAmm::get_symbol_data(&pid), reserves);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&pid));
+
+			// the migration only runs once
+			<PoolAmm as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(PoolAmm::share_total_balance(&pid), 900);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&pid));
+		})
+	}
+
+	#[test]
+	fn test_migrate_to_v2_rolls_back() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pid = PoolId::from_const_string("pool_legacy");
+			let mut shares_data = BTreeMap::new();
+			shares_data.insert(ALICE, 1000);
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 1000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 800);
+			let legacy_pool_info = crate::migration::PoolInfoV1{pid: pid.clone()
+				, total_fee: 30
+				, exchange_fee: 10
+				, shares_total_supply: 1000
+				, shares_data: shares_data
+				, symbol_data: symbol_data.clone()
+				, volume_data: VolumeData::new()
+				, description: None
+				};
+			frame_support::storage::unhashed::put(&crate::PoolInfoData::<Runtime>::hashed_key_for(&pid), &legacy_pool_info);
+			crate::PoolOwners::<Runtime>::insert(&pid, ALICE);
+			crate::StorageVersion::<Runtime>::put(crate::Releases::V1);
+
+			// the share symbol of the pool is taken by someone else, so its shares can not be migrated
+			assert_ok!(FungibleAsset::register_share_asset(&BOB, &PoolAmm::share_asset_symbol(&pid), &AssetName::default(), DEFAULT_BALANCE_PRECISION));
+
+			<PoolAmm as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(crate::StorageVersion::<Runtime>::get(), crate::Releases::V1);
+			assert_eq!(frame_support::storage::unhashed::get(&crate::PoolInfoData::<Runtime>::hashed_key_for(&pid)), Some(legacy_pool_info));
+			assert!(!crate::PoolReserves::<Runtime>::contains_key(&pid));
+			assert_eq!(PoolAmm::share_total_balance(&pid), 0);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &PoolAmm::pool_account_id(&pid)), Ok(0));
+		})
+	}
+
+	#[test]
+	fn test_single_asset_liquidity() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 1000);
+
+			assert_noop!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_AMM, &TEST_SYMBOL1, 100, u128::max_value()),
+				Error::<Runtime>::TooLessSharesAmount
+			);
+			assert_ok!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_AMM, &TEST_SYMBOL1, 100, 1));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_AMM);
+			let spent = INITIAL_BALANCE - FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB).unwrap();
+			assert!(spent > 95 && spent <= 100);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			assert_noop!(PoolAmm::remove_single_asset_liquidity_from_pool(&ALICE, &POOL_AMM, PoolAmm::share_total_balance(&POOL_AMM), &TEST_SYMBOL1, 0),
+				Error::<Runtime>::NoEnoughShares
+			);
+			assert_ok!(PoolAmm::remove_single_asset_liquidity_from_pool(&BOB, &POOL_AMM, shares, &TEST_SYMBOL1, 1));
+			assert_eq!(PoolAmm::share_balance_of(&BOB, &POOL_AMM), 0);
+			// both swaps paid the fee, so slightly less than what was put in comes back
+			let returned = FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB).unwrap() - (INITIAL_BALANCE - spent);
+			assert!(returned > 90 && returned <= spent);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 1000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 1000);
+			assert_ok!(PoolAmm::register_stable_pool(&ALICE, &POOL_STABLE, 100, 4, 0, &symbol_data, None));
+			assert_ok!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_STABLE, &TEST_SYMBOL2, 100, 1));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_STABLE);
+			assert_ok!(PoolAmm::remove_single_asset_liquidity_from_pool(&BOB, &POOL_STABLE, shares, &TEST_SYMBOL2, 95));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_STABLE));
+		})
+	}
+
+	#[test]
+	fn test_protocol_fees() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+			let shares = PoolAmm::share_balance_of(&ALICE, &POOL_AMM);
+
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 2000, &TEST_SYMBOL2, 1));
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 1000, &TEST_SYMBOL1, 1));
+			// the exchange fee is 10 of 10000, charged on the input outside of the reserves
+			let fees = PoolAmm::get_protocol_fees(&POOL_AMM);
+			assert_eq!(fees.get(&TEST_SYMBOL1), Some(&2));
+			assert_eq!(fees.get(&TEST_SYMBOL2), Some(&1));
+			assert_eq!(PoolAmm::share_balance_of(&BOB, &POOL_AMM), 0);
+			assert_eq!(PoolAmm::share_total_balance(&POOL_AMM), shares);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			assert_noop!(PoolAmm::claim_protocol_fees(Origin::signed(BOB), POOL_AMM, TEST_SYMBOL1),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_ok!(PoolAmm::claim_protocol_fees(Origin::signed(ALICE), POOL_AMM, TEST_SYMBOL1));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &TREASURY), Ok(2));
+			assert_eq!(PoolAmm::protocol_fees(&POOL_AMM, &TEST_SYMBOL1), 0);
+			assert_noop!(PoolAmm::claim_protocol_fees(Origin::signed(ALICE), POOL_AMM, TEST_SYMBOL1),
+				Error::<Runtime>::NoProtocolFees
+			);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(&ALICE, &POOL_AMM, shares, &SymbolData::new()));
+			assert_noop!(PoolAmm::unregister_pool(&ALICE, &POOL_AMM),
+				Error::<Runtime>::HasUnclaimedProtocolFees
+			);
+			assert_ok!(PoolAmm::claim_protocol_fees(Origin::root(), POOL_AMM, TEST_SYMBOL2));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &TREASURY), Ok(1));
+			assert_ok!(PoolAmm::unregister_pool(&ALICE, &POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_flash_swap() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+
+			let amount_in = PoolAmm::get_swap_input_for_output(&POOL_AMM, &TEST_SYMBOL1, 100, &TEST_SYMBOL2).unwrap();
+			assert_noop!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, amount_in - 1, b""),
+				Error::<Runtime>::TooLargeSwapAmount
+			);
+			assert_noop!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, amount_in, b"spend"),
+				Error::<Runtime>::FlashSwapNotRepaid
+			);
+			assert_noop!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, amount_in, b"reenter"),
+				Error::<Runtime>::PoolLocked
+			);
+
+			assert_ok!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 100, &TEST_SYMBOL1, amount_in, b""));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - amount_in));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + 100));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			// borrowing and repaying the same symbol pays the total fee of 30 / 10000, rounded up
+			assert_ok!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL2, 100, &TEST_SYMBOL2, 101, b""));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE + 99));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 10, &TEST_SYMBOL2, 1));
+		})
+	}
+
+	#[test]
+	fn test_flash_loan_protocol_fee() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 5000, 5000);
+			assert_ok!(PoolAmm::update_pool_fees(&POOL_AMM, 30, 30));
+			let reserve = PoolAmm::get_symbol_data(&POOL_AMM)[&TEST_SYMBOL1];
+
+			// the whole fee of a loan goes to the protocol, the borrowed amount itself goes back to the reserve
+			assert_ok!(PoolAmm::flash_swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 1000, &TEST_SYMBOL1, 1003, b""));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 3));
+			assert_eq!(PoolAmm::protocol_fees(&POOL_AMM, &TEST_SYMBOL1), 3);
+			assert_eq!(PoolAmm::get_symbol_data(&POOL_AMM)[&TEST_SYMBOL1], reserve);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_trade_guards() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			System::set_block_number(5);
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 1000);
+			amounts.insert(TEST_SYMBOL2.clone(), 800);
+			assert_ok!(PoolAmm::add_liquidity(Origin::signed(ALICE), POOL_AMM, amounts.clone(), Some(5), None));
+
+			assert_noop!(PoolAmm::swap(Origin::signed(BOB), POOL_AMM, TEST_SYMBOL1, 300, TEST_SYMBOL2, 1, Some(4), None),
+				Error::<Runtime>::DeadlinePassed
+			);
+			assert_noop!(PoolAmm::add_liquidity(Origin::signed(BOB), POOL_AMM, amounts.clone(), Some(4), None),
+				Error::<Runtime>::DeadlinePassed
+			);
+
+			// 300 into 1000 moves the price of DOT in KSM by about 40%
+			assert_noop!(PoolAmm::swap(Origin::signed(BOB), POOL_AMM, TEST_SYMBOL1, 300, TEST_SYMBOL2, 1, None, Some(3000)),
+				Error::<Runtime>::TooLargePriceImpact
+			);
+			assert_ok!(PoolAmm::swap(Origin::signed(BOB), POOL_AMM, TEST_SYMBOL1, 300, TEST_SYMBOL2, 1, Some(5), Some(5000)));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+
+			// proportional joins and exits keep the price and are only guarded by the deadline
+			assert_ok!(PoolAmm::add_liquidity(Origin::signed(BOB), POOL_AMM, amounts, None, Some(100)));
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_AMM);
+			assert_ok!(PoolAmm::remove_liquidity(Origin::signed(BOB), POOL_AMM, shares, SymbolData::new(), None, Some(100)));
+			assert_noop!(PoolAmm::add_liquidity_single_asset(Origin::signed(BOB), POOL_AMM, TEST_SYMBOL1, 500, 1, None, Some(100)),
+				Error::<Runtime>::TooLargePriceImpact
+			);
+			// an exit into DOT sells KSM for it
+			let shares = PoolAmm::share_balance_of(&ALICE, &POOL_AMM);
+			assert_noop!(PoolAmm::remove_liquidity_single_asset(Origin::signed(ALICE), POOL_AMM, shares / 4, TEST_SYMBOL1, 1, None, Some(100)),
+				Error::<Runtime>::TooLargePriceImpact
+			);
+		})
+	}
+
+	#[test]
+	fn test_pool_status() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			use crate::PoolStatus;
+			test_add_liquidity(&ALICE, 1000, 800);
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 100);
+			amounts.insert(TEST_SYMBOL2.clone(), 80);
+
+			assert_noop!(PoolAmm::set_pool_status(Origin::signed(BOB), POOL_AMM, PoolStatus::Frozen),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_ok!(PoolAmm::set_pool_status(Origin::signed(ALICE), POOL_AMM, PoolStatus::SwapsPaused));
+			assert_noop!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 10, &TEST_SYMBOL2, 1),
+				Error::<Runtime>::SwapsPaused
+			);
+			assert_noop!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, &POOL_AMM, &TEST_SYMBOL1, 100, 1),
+				Error::<Runtime>::SwapsPaused
+			);
+			assert_ok!(PoolAmm::add_liquidity_to_pool(&BOB, &POOL_AMM, &amounts));
+
+			assert_ok!(PoolAmm::set_pool_status(Origin::root(), POOL_AMM, PoolStatus::WithdrawOnly));
+			assert_noop!(PoolAmm::add_liquidity_to_pool(&BOB, &POOL_AMM, &amounts),
+				Error::<Runtime>::DepositsPaused
+			);
+			let shares = PoolAmm::share_balance_of(&BOB, &POOL_AMM);
+			assert_ok!(PoolAmm::remove_liquidity_from_pool(&BOB, &POOL_AMM, shares / 2, &SymbolData::new()));
+
+			assert_ok!(PoolAmm::set_pool_status(Origin::root(), POOL_AMM, PoolStatus::Frozen));
+			assert_noop!(PoolAmm::remove_liquidity_from_pool(&BOB, &POOL_AMM, shares / 2, &SymbolData::new()),
+				Error::<Runtime>::PoolFrozen
+			);
+
+			assert_ok!(PoolAmm::set_pool_status(Origin::signed(ALICE), POOL_AMM, PoolStatus::Active));
+			assert_ok!(PoolAmm::swap_asset(&BOB, &POOL_AMM, &TEST_SYMBOL1, 10, &TEST_SYMBOL2, 1));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL_AMM));
+		})
+	}
+
+	#[test]
+	fn test_pool_parameters() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_noop!(PoolAmm::update_fees(Origin::signed(BOB), POOL_AMM, 50, 20),
+				Error::<Runtime>::InvalidOwner
+			);
+			assert_noop!(PoolAmm::update_fees(Origin::signed(ALICE), POOL_AMM, 50, 60),
+				Error::<Runtime>::TooLargeFee
+			);
+			assert_ok!(PoolAmm::update_fees(Origin::signed(ALICE), POOL_AMM, 50, 20));
+			assert_eq!(PoolAmm::get_total_fee(&POOL_AMM), 50);
+			assert_eq!(PoolAmm::pool_info_data(&POOL_AMM).exchange_fee, 20);
+
+			let description = Some(StdString::from_string("DOT and KSM"));
+			assert_ok!(PoolAmm::update_description(Origin::root(), POOL_AMM, description.clone()));
+			assert_eq!(PoolAmm::pool_info_data(&POOL_AMM).description, description);
+
+			assert_ok!(PoolAmm::propose_owner(Origin::signed(ALICE), POOL_AMM, BOB));
+			assert_eq!(PoolAmm::get_pool_owner(&POOL_AMM), Some(ALICE));
+			assert_noop!(PoolAmm::accept_ownership(Origin::signed(ALICE), POOL_AMM),
+				Error::<Runtime>::NotPendingOwner
+			);
+			assert_ok!(PoolAmm::accept_ownership(Origin::signed(BOB), POOL_AMM));
+			assert_eq!(PoolAmm::get_pool_owner(&POOL_AMM), Some(BOB));
+			assert_eq!(PoolAmm::pending_pool_owners(&POOL_AMM), None);
+			assert_noop!(PoolAmm::update_fees(Origin::signed(ALICE), POOL_AMM, 30, 10),
+				Error::<Runtime>::InvalidOwner
+			);
+		})
+	}
+
+	#[test]
+	fn test_add_liquidity_to_empty_reserve() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			test_add_liquidity(&ALICE, 1000, 800);
+			crate::PoolReserves::<Runtime>::mutate(&POOL_AMM, |reserves| reserves.insert(TEST_SYMBOL2.clone(), 0));
+
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 100);
+			amounts.insert(TEST_SYMBOL2.clone(), 100);
+			assert_noop!(PoolAmm::add_liquidity_to_pool(&BOB, &POOL_AMM, &amounts),
+				Error::<Runtime>::NoEnoughLiquidity
+			);
+		})
+	}
+
+	#[test]
+	fn test_share_asset_deposit() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			AssetDeposit::set(&100);
+			let pid = PoolId::from_const_string("pool_new");
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 0);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 0);
+			assert_noop!(PoolAmm::register_pool(&BOB, &pid, 30, 10, &symbol_data, None),
+				pallet_balances::Error::<Runtime>::InsufficientBalance
+			);
+
+			assert_ok!(Currencies::deposit(DEFAULT_SYMBOL, &BOB, 1000));
+			assert_ok!(PoolAmm::register_pool(&BOB, &pid, 30, 10, &symbol_data, None));
+			assert_eq!(PoolAmm::share_asset_deposits(&pid), Some((BOB, 100)));
+			assert_eq!(Currencies::reserved_balance(DEFAULT_SYMBOL, &BOB), 100);
+
+			// the deposit goes back to the registrant along with the share asset
+			assert_ok!(PoolAmm::unregister_pool(&BOB, &pid));
+			assert_eq!(PoolAmm::share_asset_deposits(&pid), None);
+			assert_eq!(Currencies::reserved_balance(DEFAULT_SYMBOL, &BOB), 0);
+			assert_eq!(Currencies::free_balance(DEFAULT_SYMBOL, &BOB), 1000);
+			assert!(!FungibleAsset::is_asset_existed(&PoolAmm::share_asset_symbol(&pid)));
+		})
+	}
+
+	#[test]
+	fn test_share_symbol_reserved() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pid = PoolId::from_const_string("pool_new");
+			let share_symbol = PoolAmm::share_asset_symbol(&pid);
+			assert_noop!(FungibleAsset::register_asset(&BOB, &share_symbol, &AssetName::default(), DEFAULT_BALANCE_PRECISION, true, true, None, None, 0),
+				pallet_fungible_asset::Error::<Runtime>::ReservedAssetSymbol
+			);
+			assert_noop!(FungibleAsset::register_asset_with_deposit(&BOB, &share_symbol, &AssetName::default(), DEFAULT_BALANCE_PRECISION, true, true, None, None, 0),
+				pallet_fungible_asset::Error::<Runtime>::ReservedAssetSymbol
+			);
+
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 0);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 0);
+			assert_ok!(PoolAmm::register_pool(&ALICE, &pid, 30, 10, &symbol_data, None));
+			assert!(FungibleAsset::is_asset_owner(&share_symbol, &PoolAmm::pool_account_id(&pid)));
+
+			// a pool id has to fit behind the prefix in full, or two pools could share a symbol
+			assert_noop!(PoolAmm::register_pool(&ALICE, &PoolId::from_const_string("pool_new_longer"), 30, 10, &symbol_data, None),
+				Error::<Runtime>::InvalidPoolId
+			);
+		})
+	}
+
+	/// Joins `pid` with `amount` of TEST_SYMBOL1 alone and exits on both symbols, returns the TEST_SYMBOL1 spent
+	/// on the way and the protocol fees booked for it
+	fn join_and_exit(pid: &PoolId, amount: Balance) -> (Balance, Balance) {
+		assert_ok!(PoolAmm::add_single_asset_liquidity_to_pool(&BOB, pid, &TEST_SYMBOL1, amount, 1));
+		let shares = PoolAmm::share_balance_of(&BOB, pid);
+		assert_ok!(PoolAmm::remove_liquidity_from_pool(&BOB, pid, shares, &SymbolData::new()));
+		assert_ok!(PoolAmm::ensure_reserves_consistent(pid));
+		let spent = INITIAL_BALANCE - FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB).unwrap();
+		(spent, PoolAmm::protocol_fees(pid, &TEST_SYMBOL1))
+	}
+
+	#[test]
+	fn test_single_asset_join_protocol_fee() {
+		let pid = PoolId::from_const_string("pool_fee");
+		let mut symbol_data: SymbolData = BTreeMap::new();
+		symbol_data.insert(TEST_SYMBOL1.clone(), 5000);
+		symbol_data.insert(TEST_SYMBOL2.clone(), 5000);
+
+		// the whole fee goes to the protocol, so the fees booked are easy to compare
+		let mut route = (0, 0);
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(PoolAmm::register_pool(&ALICE, &pid, 300, 300, &symbol_data, None));
+			route = join_and_exit(&pid, 2000);
+		});
+		let (spent, route_fee) = route;
+		assert!(spent > 0);
+
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(PoolAmm::register_pool(&ALICE, &pid, 300, 300, &symbol_data, None));
+			assert_ok!(PoolAmm::swap_asset(&BOB, &pid, &TEST_SYMBOL1, spent, &TEST_SYMBOL2, 1));
+			let swap_fee = PoolAmm::protocol_fees(&pid, &TEST_SYMBOL1);
+			assert_eq!(swap_fee, spent * 300 / 10_000);
+			// joining with one symbol and leaving with both costs the protocol fee of the swap it stands for
+			assert!(route_fee > 0 && route_fee + 1 >= swap_fee && route_fee <= swap_fee);
+		});
+	}
+
+	#[test]
+	fn test_single_asset_exit_protocol_fee() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let pid = PoolId::from_const_string("pool_fee");
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 5000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 5000);
+			assert_ok!(PoolAmm::register_pool(&ALICE, &pid, 300, 300, &symbol_data, None));
+			let mut amounts: SymbolData = BTreeMap::new();
+			amounts.insert(TEST_SYMBOL1.clone(), 1000);
+			amounts.insert(TEST_SYMBOL2.clone(), 1000);
+			assert_ok!(PoolAmm::add_liquidity_to_pool(&BOB, &pid, &amounts));
+
+			// the KSM part of the exit is sold for DOT and pays the exchange fee of that swap
+			let shares = PoolAmm::share_balance_of(&BOB, &pid);
+			assert_ok!(PoolAmm::remove_single_asset_liquidity_from_pool(&BOB, &pid, shares, &TEST_SYMBOL1, 1));
+			let sold = PoolAmm::pool_volumes(&pid)[&TEST_SYMBOL2].input;
+			assert!(sold > 0);
+			assert_eq!(PoolAmm::protocol_fees(&pid, &TEST_SYMBOL2), sold * 300 / 10_000);
+			assert_eq!(PoolAmm::protocol_fees(&pid, &TEST_SYMBOL1), 0);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&pid));
+		})
+	}
 }
@@ -0,0 +1,124 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+//! Weighted product invariant of Balancer: prod(B_k ^ (w_k / W)) = V
+
+use sp_runtime::{FixedPointNumber, FixedU128};
+use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Saturating, Zero};
+
+pub const MAX_WEIGHTED_SYMBOLS: usize = 8;
+/// A swap may bring in at most half of the input reserve
+pub const MAX_IN_RATIO: u128 = 2;
+/// A swap may take out at most a third of the output reserve
+pub const MAX_OUT_RATIO: u128 = 3;
+
+const MAX_ITERATIONS: u32 = 100;
+/// 1e-10, where the series of the fractional power stops
+const PRECISION: u128 = 100_000_000;
+
+/// `base ^ exp` for a base in (0, 2), the integer part of `exp` is exact and the fractional part
+/// is approximated by the binomial series
+pub fn pow(base: FixedU128, exp: FixedU128) -> Option<FixedU128> {
+	if base.is_zero() || base >= FixedU128::saturating_from_integer(2) {
+		return None;
+	}
+
+	let whole = exp.trunc();
+	let whole_pow = base.saturating_pow((whole.into_inner() / FixedU128::accuracy()) as usize);
+	let remain = exp.checked_sub(&whole)?;
+	if remain.is_zero() {
+		return Some(whole_pow);
+	}
+	whole_pow.checked_mul(&pow_approx(base, remain)?)
+}
+
+fn pow_approx(base: FixedU128, exp: FixedU128) -> Option<FixedU128> {
+	let one = FixedU128::one();
+	let (x, x_negative) = if base >= one { (base - one, false) } else { (one - base, true) };
+
+	let mut term = one;
+	let mut sum = one;
+	let mut negative = false;
+	for i in 1..MAX_ITERATIONS {
+		let k = FixedU128::saturating_from_integer(i);
+		let k_prev = k.saturating_sub(one);
+		let (c, c_negative) = if exp >= k_prev { (exp - k_prev, false) } else { (k_prev - exp, true) };
+		term = term.checked_mul(&c)?.checked_mul(&x)?.checked_div(&k)?;
+		if term.into_inner() < PRECISION {
+			break;
+		}
+
+		if x_negative {
+			negative = !negative;
+		}
+		if c_negative {
+			negative = !negative;
+		}
+		sum = if negative { sum.checked_sub(&term)? } else { sum.checked_add(&term)? };
+	}
+	Some(sum)
+}
+
+/// Output for `amount_in`, the fee is taken from the input
+pub fn calc_out_given_in(
+	balance_in: u128,
+	weight_in: u32,
+	balance_out: u128,
+	weight_out: u32,
+	amount_in: u128,
+	fee: FixedU128,
+) -> Option<u128> {
+	let amount_in = FixedU128::one().checked_sub(&fee)?.saturating_mul_int(amount_in);
+	let base = FixedU128::checked_from_rational(balance_in, balance_in.checked_add(amount_in)?)?;
+	let ratio = pow(base, FixedU128::checked_from_rational(weight_in, weight_out)?)?;
+	Some(FixedU128::one().checked_sub(&ratio)?.saturating_mul_int(balance_out))
+}
+
+/// Input needed for `amount_out` before the fee is added, rounded up
+pub fn calc_in_given_out(
+	balance_in: u128,
+	weight_in: u32,
+	balance_out: u128,
+	weight_out: u32,
+	amount_out: u128,
+	fee: FixedU128,
+) -> Option<u128> {
+	let base = FixedU128::checked_from_rational(balance_out, balance_out.checked_sub(amount_out)?)?;
+	let ratio = pow(base, FixedU128::checked_from_rational(weight_out, weight_in)?)?;
+	let amount_in = ratio.checked_sub(&FixedU128::one())?
+		.checked_div(&FixedU128::one().checked_sub(&fee)?)?
+		.saturating_mul_int(balance_in);
+	amount_in.checked_add(1)
+}
+
+/// `V' / V` once a single reserve moves from `prev_balance` to `new_balance`
+pub fn calc_invariant_ratio(
+	prev_balance: u128,
+	new_balance: u128,
+	weight: u32,
+	total_weight: u32,
+) -> Option<FixedU128> {
+	pow(
+		FixedU128::checked_from_rational(new_balance, prev_balance)?,
+		FixedU128::checked_from_rational(weight, total_weight)?,
+	)
+}
+
+/// Shares minted for depositing `amount_in` of a single symbol, part of it being swapped for the others pays the fee
+pub fn calc_shares_given_single_in(
+	balance_in: u128,
+	weight_in: u32,
+	total_weight: u32,
+	shares_total_supply: u128,
+	amount_in: u128,
+	fee: FixedU128,
+) -> Option<u128> {
+	let normalized_weight = FixedU128::checked_from_rational(weight_in, total_weight)?;
+	let fee = FixedU128::one().checked_sub(&normalized_weight)?.checked_mul(&fee)?;
+	let amount_in = FixedU128::one().checked_sub(&fee)?.saturating_mul_int(amount_in);
+	let base = FixedU128::checked_from_rational(balance_in.checked_add(amount_in)?, balance_in)?;
+	let ratio = pow(base, normalized_weight)?;
+	Some(ratio.checked_sub(&FixedU128::one())?.saturating_mul_int(shares_total_supply))
+}
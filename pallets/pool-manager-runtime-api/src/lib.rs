@@ -0,0 +1,39 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::vec::Vec;
+
+pub use base::*;
+
+sp_api::decl_runtime_apis! {
+	/// Quotes and route search over all the pools of the pool manager, to be run off-chain
+	pub trait PoolManagerApi {
+		/// Output of swapping `amount_in` of `asset_in` along `path`, None if the path can not be swapped
+		fn get_swap_return_with_path(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			path: SwapPath,
+		) -> Option<Balance>;
+
+		/// The path of at most `max_hops` hops with the highest output and that output, the search is capped in hops and paths
+		fn find_best_route(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			max_hops: u32,
+		) -> Option<(SwapPath, Balance)>;
+
+		/// `amount_in` split in at most `MAX_SPLIT_PARTS` parts over paths not sharing a pool, as (path, input, output)
+		fn find_best_split_route(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			max_hops: u32,
+			parts: u32,
+		) -> Vec<(SwapPath, Balance, Balance)>;
+	}
+}
@@ -5,16 +5,37 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[allow(unused_imports)]
-extern crate alloc;
-
-use frame_support::{ensure};
-use frame_support::dispatch::{DispatchResult};
-use sp_std::vec::Vec;
-
-pub use base::*;
-
+#[allow(unused_imports)]
+extern crate alloc;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::{ensure, transactional};
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use sp_std::vec::Vec;
+use sp_std::vec;
+use sp_std::cmp::min;
+use sp_std::collections::btree_set::BTreeSet;
+use pallet_pool_amm::{PoolCurve, PoolLifecycleHandler, WeightData};
+
+pub use base::*;
+
 type PoolAmm<T> = pallet_pool_amm::Pallet<T>;
+type OrderBook<T> = pallet_order_book::Pallet<T>;
+
+pub const MAX_SWAP_HOPS: usize = 8;
+/// Hops of the paths the route search looks at
+pub const MAX_ROUTE_HOPS: usize = 3;
+/// Paths the route search collects at most
+pub const MAX_ROUTES: usize = 16;
+/// Hop quotes the route search spends at most before it stops
+pub const MAX_ROUTE_QUOTES: usize = 512;
+/// Parts a split route divides its input in at most
+pub const MAX_SPLIT_PARTS: u32 = 20;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -24,7 +45,7 @@ pub mod pallet {
 
 	#[pallet::config]
     pub trait Config:
-        frame_system::Config + pallet_pool_amm::Config
+        frame_system::Config + pallet_pool_amm::Config + pallet_order_book::Config
     {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 	}
@@ -36,8 +57,241 @@ pub mod pallet {
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
 
-	#[pallet::call]
+	#[pallet::call]
 	impl<T: Config> Pallet<T> {
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn create_amm(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			total_fee: u32,
+			exchange_fee: u32,
+			symbol_data: SymbolData,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::create_amm_pool(
+				&issuer,
+				&pid,
+				total_fee,
+				exchange_fee,
+				&symbol_data,
+				description,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(2, 4))]
+		pub fn create_stable(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			amplification: u32,
+			total_fee: u32,
+			exchange_fee: u32,
+			symbol_data: SymbolData,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::create_stable_pool(
+				&issuer,
+				&pid,
+				amplification,
+				total_fee,
+				exchange_fee,
+				&symbol_data,
+				description,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(2, 5))]
+		pub fn create_weighted(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			weights: WeightData,
+			total_fee: u32,
+			exchange_fee: u32,
+			symbol_data: SymbolData,
+			description: Option<StdString>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::create_weighted_pool(
+				&issuer,
+				&pid,
+				&weights,
+				total_fee,
+				exchange_fee,
+				&symbol_data,
+				description,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn create_order_book(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			base: AssetSymbol,
+			quote: AssetSymbol,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::create_order_book_pool(
+				&issuer,
+				&pid,
+				&base,
+				&quote,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn destroy(
+			origin: OriginFor<T>,
+			pid: PoolId,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			Self::destroy_pool(
+				&issuer,
+				&pid,
+			)?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn add_liquidity(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			amounts: SymbolData,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			PoolAmm::<T>::with_trade_guards(&pid, deadline, max_price_impact, None, || Self::add_liquidity_to_pool(
+					&issuer,
+					&pid,
+					&amounts,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(3, 2) + PoolAmm::<T>::price_observation_weight(1)
+			+ PoolAmm::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn add_liquidity_single_asset(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset: AssetSymbol,
+			amount: Balance,
+			min_shares: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			let pair = PoolAmm::<T>::get_paired_symbol(&pid, &asset).map(|other| (asset, other));
+			PoolAmm::<T>::with_trade_guards(&pid, deadline, max_price_impact, pair, || Self::add_single_asset_liquidity_to_pool(
+					&issuer,
+					&pid,
+					&asset,
+					amount,
+					min_shares,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(1_000_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn remove_liquidity(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			shares: Balance,
+			amounts: SymbolData,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			PoolAmm::<T>::with_trade_guards(&pid, deadline, max_price_impact, None, || Self::remove_liquidity_from_pool(
+					&issuer,
+					&pid,
+					shares,
+					&amounts,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(3, 2)
+			+ PoolAmm::<T>::price_observation_weight(pallet_pool_amm::MAX_NUM_SYMBOLS as u64 - 1)
+			+ PoolAmm::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn remove_liquidity_single_asset(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			shares: Balance,
+			asset: AssetSymbol,
+			min_amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			let pair = PoolAmm::<T>::get_paired_symbol(&pid, &asset).map(|other| (other, asset));
+			PoolAmm::<T>::with_trade_guards(&pid, deadline, max_price_impact, pair, || Self::remove_single_asset_liquidity_from_pool(
+					&issuer,
+					&pid,
+					shares,
+					&asset,
+					min_amount_out,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(3, 3) + PoolAmm::<T>::price_observation_weight(1)
+			+ PoolAmm::<T>::trade_guard_weight(max_price_impact.is_some()))]
+		pub fn swap(
+			origin: OriginFor<T>,
+			pid: PoolId,
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			min_amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			PoolAmm::<T>::with_trade_guards(&pid, deadline, max_price_impact, Some((asset_in, asset_out)), || Self::swap_asset_in_pool(
+					&issuer,
+					&pid,
+					&asset_in,
+					amount_in,
+					&asset_out,
+					min_amount_out,
+			))?;
+			Ok(().into())
+		}
+
+		#[pallet::weight(2_000_000 + T::DbWeight::get().reads_writes(3, 3) * path.len() as u64
+			+ PoolAmm::<T>::price_observation_weight(path.len() as u64)
+			+ PoolAmm::<T>::trade_guard_weight(max_price_impact.is_some()) * path.len() as u64)]
+		pub fn swap_path(
+			origin: OriginFor<T>,
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			path: SwapPath,
+			min_amount_out: Balance,
+			deadline: Option<T::BlockNumber>,
+			max_price_impact: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			let issuer = ensure_signed(origin)?;
+			if let Some(deadline) = deadline {
+				ensure!(
+					<frame_system::Pallet<T>>::block_number() <= deadline,
+					pallet_pool_amm::Error::<T>::DeadlinePassed
+				);
+			}
+			Self::swap_with_path(
+				&issuer,
+				&asset_in,
+				amount_in,
+				&path,
+				min_amount_out,
+				max_price_impact,
+			)?;
+			Ok(().into())
+		}
 	}
 	
 	#[pallet::storage]
@@ -53,7 +307,14 @@ pub mod pallet {
 	#[pallet::error]
     pub enum Error<T> {
 		PoolNotExists,
-		PoolAlreadyExists,
+		PoolAlreadyExists,
+		EmptySwapPath,
+		TooManySwapHops,
+		InvalidSwapPath,
+		ZeroAmount,
+		NoEnoughSwapAmount,
+		PoolTypeMismatch,
+		UnsupportedPoolType,
 	}
 
 	#[pallet::event]
@@ -61,6 +322,10 @@ pub mod pallet {
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
 		AmmPoolRegistered(T::PoolId),
+		StablePoolRegistered(T::PoolId),
+		WeightedPoolRegistered(T::PoolId),
+		OrderBookPoolRegistered(T::PoolId),
+		SwapWithPath(T::AccountId, AssetSymbol, Balance, AssetSymbol, Balance),
 	}
 
 	#[pallet::genesis_config]
@@ -80,10 +345,15 @@ pub mod pallet {
         }
 	}
 
-	#[pallet::genesis_build]
-    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
-        fn build(&self) {
-		}
+	#[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+			// the pools are registered by the genesis of pallet_pool_amm, which is built first
+			self.endowed_pool.iter().cloned().for_each(|(pid, pool_type)| {
+				Pallet::<T>::register_existing_pool(&FixedString(pid.to_fixed_array()), &pool_type)
+					.expect("GenesisBuild: Failed to register pool.");
+			})
+		}
 	}
 }
 
@@ -117,13 +387,130 @@ impl<T: Config> Pallet<T> {
         Ok(())
 	}
 
+	/// Creates a pool on the StableSwap invariant, suited to pairs trading close to parity
+	pub fn create_stable_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		amplification: u32,
+		total_fee: u32,
+		exchange_fee: u32,
+		symbol_data: &SymbolData,
+		description: Option<StdString>,
+	) -> DispatchResult {
+		ensure!(
+			PoolInstances::<T>::get(pid) == PoolType::None,
+			Error::<T>::PoolAlreadyExists
+		);
+
+		PoolAmm::<T>::register_stable_pool(
+			issuer,
+			pid,
+			amplification,
+			total_fee,
+			exchange_fee,
+			symbol_data,
+			description,
+		)?;
+
+		PoolInstances::<T>::insert(pid.clone(), PoolType::StablePool);
+		Self::deposit_event(Event::StablePoolRegistered(PoolAmm::<T>::get_pool_id(pid)));
+        Ok(())
+	}
+
+	/// Creates a pool on the weighted product invariant, each symbol priced by its weight
+	pub fn create_weighted_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		weights: &WeightData,
+		total_fee: u32,
+		exchange_fee: u32,
+		symbol_data: &SymbolData,
+		description: Option<StdString>,
+	) -> DispatchResult {
+		ensure!(
+			PoolInstances::<T>::get(pid) == PoolType::None,
+			Error::<T>::PoolAlreadyExists
+		);
+
+		PoolAmm::<T>::register_weighted_pool(
+			issuer,
+			pid,
+			weights,
+			total_fee,
+			exchange_fee,
+			symbol_data,
+			description,
+		)?;
+
+		PoolInstances::<T>::insert(pid.clone(), PoolType::WeightedPool);
+		Self::deposit_event(Event::WeightedPoolRegistered(PoolAmm::<T>::get_pool_id(pid)));
+        Ok(())
+	}
+
+	/// Creates a central limit order book of `base` priced in `quote`, it has no liquidity shares
+	pub fn create_order_book_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		base: &AssetSymbol,
+		quote: &AssetSymbol,
+	) -> DispatchResult {
+		ensure!(
+			PoolInstances::<T>::get(pid) == PoolType::None,
+			Error::<T>::PoolAlreadyExists
+		);
+
+		OrderBook::<T>::create_order_book(issuer, pid, base, quote)?;
+
+		PoolInstances::<T>::insert(pid.clone(), PoolType::OrderBook);
+		Self::deposit_event(Event::OrderBookPoolRegistered(PoolAmm::<T>::get_pool_id(pid)));
+        Ok(())
+	}
+
+	/// Adds a pool already registered in its pallet, which has to agree with `pool_type`
+	pub fn register_existing_pool(
+		pid: &PoolId,
+		pool_type: &PoolType,
+	) -> DispatchResult {
+		match pool_type {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
+				PoolAmm::<T>::ensure_pool_exists(pid)?;
+				ensure!(
+					Self::get_pool_type_of_curve(PoolAmm::<T>::get_pool_curve(pid)) == *pool_type,
+					Error::<T>::PoolTypeMismatch
+				);
+				PoolInstances::<T>::insert(pid.clone(), pool_type.clone());
+				Ok(())
+			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::ensure_order_book_exists(pid)?;
+				PoolInstances::<T>::insert(pid.clone(), PoolType::OrderBook);
+				Ok(())
+			}
+			PoolType::None => Err(Error::<T>::PoolNotExists.into())
+		}
+	}
+
+	pub fn get_pool_type_of_curve(curve: PoolCurve) -> PoolType {
+		match curve {
+			PoolCurve::ConstantProduct => PoolType::AmmPool,
+			PoolCurve::StableSwap(_) => PoolType::StablePool,
+			PoolCurve::Weighted => PoolType::WeightedPool,
+		}
+	}
+
 	pub fn destroy_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
 	) -> DispatchResult {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::unregister_pool(issuer, pid)?;
+				PoolInstances::<T>::remove(pid);
+				Ok(())
+			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::destroy_order_book(issuer, pid)?;
+				PoolInstances::<T>::remove(pid);
 				Ok(())
 			}
 			PoolType::None => Err(Error::<T>::PoolNotExists.into())
@@ -136,14 +523,49 @@ impl<T: Config> Pallet<T> {
 		amounts: &SymbolData,
 	) -> DispatchResult {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::add_liquidity_to_pool(issuer, pid, amounts)?;
 				Ok(())
 			}
+			PoolType::OrderBook => Err(Error::<T>::UnsupportedPoolType.into()),
 			PoolType::None => Err(Error::<T>::PoolNotExists.into())
 		}
 	}
 
+	pub fn add_single_asset_liquidity_to_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		asset: &AssetSymbol,
+		amount: Balance,
+		min_shares: Balance,
+	) -> DispatchResult {
+		match PoolInstances::<T>::get(pid){
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
+				PoolAmm::<T>::add_single_asset_liquidity_to_pool(issuer, pid, asset, amount, min_shares)?;
+				Ok(())
+			}
+			PoolType::OrderBook => Err(Error::<T>::UnsupportedPoolType.into()),
+			PoolType::None => Err(Error::<T>::PoolNotExists.into())
+		}
+	}
+
+	pub fn remove_single_asset_liquidity_from_pool(
+		issuer: &T::AccountId,
+		pid: &PoolId,
+		shares: Balance,
+		asset: &AssetSymbol,
+		min_amount_out: Balance,
+	) -> DispatchResult {
+		match PoolInstances::<T>::get(pid){
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
+				PoolAmm::<T>::remove_single_asset_liquidity_from_pool(issuer, pid, shares, asset, min_amount_out)?;
+				Ok(())
+			}
+			PoolType::OrderBook => Err(Error::<T>::UnsupportedPoolType.into()),
+			PoolType::None => Err(Error::<T>::PoolNotExists.into())
+		}
+	}
+
 	pub fn remove_liquidity_from_pool(
 		issuer: &T::AccountId,
 		pid: &PoolId,
@@ -151,10 +573,11 @@ impl<T: Config> Pallet<T> {
 		amounts: &SymbolData,
 	) -> DispatchResult {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::remove_liquidity_from_pool(issuer, pid, shares, amounts)?;
 				Ok(())
 			}
+			PoolType::OrderBook => Err(Error::<T>::UnsupportedPoolType.into()),
 			PoolType::None => Err(Error::<T>::PoolNotExists.into())
 		}
 	}
@@ -166,9 +589,12 @@ impl<T: Config> Pallet<T> {
 		asset_out: &AssetSymbol,
 	) -> Balance {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::get_swap_return_asset(pid, asset_in, amount_in, asset_out)
 			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::get_swap_return_asset(pid, asset_in, amount_in, asset_out)
+			}
 			PoolType::None => 0
 		}
 	}
@@ -182,23 +608,282 @@ impl<T: Config> Pallet<T> {
 		min_amount_out: Balance,
 	) -> DispatchResult {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::swap_asset(who, pid, asset_in, amount_in, asset_out, min_amount_out)?;
 				Ok(())
 			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::swap_asset(who, pid, asset_in, amount_in, asset_out, min_amount_out)?;
+				Ok(())
+			}
 			PoolType::None => Err(Error::<T>::PoolNotExists.into())
 		}
 	}
 
+	pub fn is_swappable_in_pool(
+		pid: &PoolId,
+		asset_in: &AssetSymbol,
+		asset_out: &AssetSymbol,
+	) -> bool {
+		match PoolInstances::<T>::get(pid){
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
+				PoolAmm::<T>::is_swappable(pid, asset_in, asset_out)
+			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::is_swappable(pid, asset_in, asset_out)
+			}
+			PoolType::None => false
+		}
+	}
+
+	/// Quotes the final output of swapping `amount_in` of `asset_in` hop by hop along `path`
+	pub fn get_swap_return_asset_with_path(
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		path: &SwapPath,
+	) -> Result<Balance, DispatchError> {
+		ensure!(
+			path.len() > 0,
+			Error::<T>::EmptySwapPath
+		);
+		ensure!(
+			path.len() <= MAX_SWAP_HOPS,
+			Error::<T>::TooManySwapHops
+		);
+
+		let mut current_asset = asset_in.clone();
+		let mut current_amount = amount_in;
+		for (pid, asset_out) in path {
+			ensure!(
+				current_amount > 0,
+				Error::<T>::ZeroAmount
+			);
+			ensure!(
+				Self::is_swappable_in_pool(pid, &current_asset, asset_out),
+				Error::<T>::InvalidSwapPath
+			);
+			current_amount = Self::get_swap_return_asset_from_pool(pid, &current_asset, current_amount, asset_out);
+			current_asset = asset_out.clone();
+		}
+		Ok(current_amount)
+	}
+
+	/// Swaps through every hop of `path` atomically, only the final output is checked against `min_amount_out`.
+	/// With `max_price_impact` every amm hop is reverted once it moves the price of its pair further than that,
+	/// order book hops are priced by the orders they fill.
+	#[transactional]
+	pub fn swap_with_path(
+		who: &T::AccountId,
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		path: &SwapPath,
+		min_amount_out: Balance,
+		max_price_impact: Option<u32>,
+	) -> DispatchResult {
+		ensure!(
+			path.len() > 0,
+			Error::<T>::EmptySwapPath
+		);
+		ensure!(
+			path.len() <= MAX_SWAP_HOPS,
+			Error::<T>::TooManySwapHops
+		);
+
+		let mut current_asset = asset_in.clone();
+		let mut current_amount = amount_in;
+		for (pid, asset_out) in path {
+			ensure!(
+				current_amount > 0,
+				Error::<T>::ZeroAmount
+			);
+			ensure!(
+				Self::is_swappable_in_pool(pid, &current_asset, asset_out),
+				Error::<T>::InvalidSwapPath
+			);
+			let amount_out = Self::get_swap_return_asset_from_pool(pid, &current_asset, current_amount, asset_out);
+			PoolAmm::<T>::with_trade_guards(pid, None, max_price_impact, Some((current_asset.clone(), asset_out.clone())), || {
+				Self::swap_asset_in_pool(who, pid, &current_asset, current_amount, asset_out, amount_out)
+			})?;
+			current_asset = asset_out.clone();
+			current_amount = amount_out;
+		}
+
+		ensure!(
+			current_amount >= min_amount_out,
+			Error::<T>::NoEnoughSwapAmount
+		);
+		Self::deposit_event(Event::SwapWithPath(who.clone(), asset_in.clone(), amount_in, current_asset, current_amount));
+		Ok(())
+	}
+
+	/// Paths of at most `max_hops` hops from `asset_in` to `asset_out` through the pools open to swaps,
+	/// without passing a symbol twice, with the output of swapping `amount_in` along it. The search is capped
+	/// at `MAX_ROUTE_HOPS` hops, `MAX_ROUTES` paths and `MAX_ROUTE_QUOTES` hop quotes.
+	pub fn find_routes(
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		max_hops: u32,
+	) -> Vec<(SwapPath, Balance)> {
+		let pools = PoolInstances::<T>::iter()
+			.filter(|(pid, pool_type)| *pool_type != PoolType::None && PoolAmm::<T>::pool_statuses(pid).can_swap())
+			.map(|(pid, _)| {
+				let symbols = Self::get_all_symbols_from_pool(&pid);
+				(pid, symbols)
+			})
+			.collect::<Vec<(PoolId, Vec<AssetSymbol>)>>();
+
+		let mut routes = Vec::new();
+		let mut path = SwapPath::new();
+		let mut visited = vec![asset_in.clone()];
+		let mut quotes_left = MAX_ROUTE_QUOTES;
+		Self::search_routes(
+			&pools,
+			asset_in,
+			amount_in,
+			asset_out,
+			min(max_hops as usize, MAX_ROUTE_HOPS),
+			&mut path,
+			&mut visited,
+			&mut routes,
+			&mut quotes_left,
+		);
+		routes
+	}
+
+	fn search_routes(
+		pools: &[(PoolId, Vec<AssetSymbol>)],
+		current_asset: &AssetSymbol,
+		current_amount: Balance,
+		asset_out: &AssetSymbol,
+		hops_left: usize,
+		path: &mut SwapPath,
+		visited: &mut Vec<AssetSymbol>,
+		routes: &mut Vec<(SwapPath, Balance)>,
+		quotes_left: &mut usize,
+	) {
+		if hops_left == 0 || current_amount == 0 {
+			return;
+		}
+		for (pid, symbols) in pools {
+			// a pool already on the path would be quoted against reserves an earlier hop has moved
+			if !symbols.contains(current_asset) || path.iter().any(|(used_pid, _)| used_pid == pid) {
+				continue;
+			}
+			for symbol in symbols {
+				if visited.contains(symbol) || !Self::is_swappable_in_pool(pid, current_asset, symbol) {
+					continue;
+				}
+				if *quotes_left == 0 || routes.len() >= MAX_ROUTES {
+					return;
+				}
+				*quotes_left -= 1;
+				let amount_out = Self::get_swap_return_asset_from_pool(pid, current_asset, current_amount, symbol);
+				path.push((pid.clone(), symbol.clone()));
+				if symbol == asset_out {
+					routes.push((path.clone(), amount_out));
+				}
+				else {
+					visited.push(symbol.clone());
+					Self::search_routes(pools, symbol, amount_out, asset_out, hops_left - 1, path, visited, routes, quotes_left);
+					visited.pop();
+				}
+				path.pop();
+			}
+		}
+	}
+
+	/// The path with the highest output for `amount_in` among `find_routes`
+	pub fn find_best_route(
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		max_hops: u32,
+	) -> Option<(SwapPath, Balance)> {
+		Self::find_routes(asset_in, amount_in, asset_out, max_hops)
+			.into_iter()
+			.filter(|(_, amount_out)| *amount_out > 0)
+			.max_by_key(|(_, amount_out)| *amount_out)
+	}
+
+	/// Splits `amount_in` in `parts` and gives each part to the path adding the most output, as (path, input, output).
+	/// Paths sharing a pool with a path already given a part are left out, their quotes would not hold together.
+	/// `parts` is capped at `MAX_SPLIT_PARTS`, so on top of the search at most `2 * MAX_SPLIT_PARTS * MAX_ROUTES`
+	/// paths of `MAX_ROUTE_HOPS` hops are quoted.
+	pub fn find_best_split_route(
+		asset_in: &AssetSymbol,
+		amount_in: Balance,
+		asset_out: &AssetSymbol,
+		max_hops: u32,
+		parts: u32,
+	) -> Vec<(SwapPath, Balance, Balance)> {
+		let parts = min(parts, MAX_SPLIT_PARTS);
+		let routes = Self::find_routes(asset_in, amount_in, asset_out, max_hops);
+		if parts == 0 || routes.len() == 0 {
+			return Vec::new();
+		}
+
+		let quote = |path: &SwapPath, amount: Balance| {
+			Self::get_swap_return_asset_with_path(asset_in, amount, path).unwrap_or(0)
+		};
+		let part = amount_in / Balance::from(parts);
+		let mut allocations = vec![0 as Balance; routes.len()];
+		let mut used_pools = BTreeSet::new();
+		for index in 0..parts {
+			let amount = if index == parts - 1 { amount_in - part * Balance::from(parts - 1) } else { part };
+			if amount == 0 {
+				continue;
+			}
+
+			let mut best: Option<(usize, Balance)> = None;
+			for (route_index, (path, _)) in routes.iter().enumerate() {
+				let allocation = allocations[route_index];
+				if allocation == 0 && path.iter().any(|(pid, _)| used_pools.contains(pid)) {
+					continue;
+				}
+				let gain = quote(path, allocation + amount).saturating_sub(quote(path, allocation));
+				if best.map_or(true, |(_, best_gain)| gain > best_gain) {
+					best = Some((route_index, gain));
+				}
+			}
+			if let Some((route_index, _)) = best {
+				allocations[route_index] += amount;
+				used_pools.extend(routes[route_index].0.iter().map(|(pid, _)| pid.clone()));
+			}
+		}
+
+		routes.into_iter().zip(allocations.into_iter())
+			.filter(|(_, allocation)| *allocation > 0)
+			.map(|((path, _), allocation)| {
+				let amount_out = quote(&path, allocation);
+				(path, allocation, amount_out)
+			})
+			.collect()
+	}
+
+	pub fn get_all_symbols_from_pool(
+		pid: &PoolId,
+	) -> Vec<AssetSymbol> {
+		match PoolInstances::<T>::get(pid){
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
+				PoolAmm::<T>::get_all_symbols(pid)
+			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::get_all_symbols(pid)
+			}
+			PoolType::None => Vec::new()
+		}
+	}
+
 	pub fn share_balance_of_pool(
 		who: &T::AccountId,
 		pid: &PoolId,
 	) -> Balance {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::share_balance_of(who, pid)
 			}
-			PoolType::None => 0
+			PoolType::OrderBook | PoolType::None => 0
 		}
 	}
 
@@ -206,10 +891,10 @@ impl<T: Config> Pallet<T> {
 		pid: &PoolId,
 	) -> Balance {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::share_total_balance(pid)
 			}
-			PoolType::None => 0
+			PoolType::OrderBook | PoolType::None => 0
 		}
 	}
 
@@ -217,10 +902,10 @@ impl<T: Config> Pallet<T> {
 		pid: &PoolId,
 	) -> u32 {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::get_total_fee(pid)
 			}
-			PoolType::None => 0
+			PoolType::OrderBook | PoolType::None => 0
 		}
 	}
 
@@ -228,10 +913,36 @@ impl<T: Config> Pallet<T> {
 		pid: &PoolId,
 	) -> VolumeData {
 		match PoolInstances::<T>::get(pid){
-			PoolType::AmmPool => {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => {
 				PoolAmm::<T>::get_volume_data(pid)
 			}
+			PoolType::OrderBook => {
+				OrderBook::<T>::get_volume_data(pid)
+			}
 			PoolType::None => VolumeData::new()
 		}
 	}
 }
+
+/// Keeps `PoolInstances` in step with the pools registered directly in pallet_pool_amm
+impl<T: Config> PoolLifecycleHandler for Pallet<T> {
+	fn ensure_can_register(pid: &PoolId) -> DispatchResult {
+		ensure!(
+			PoolInstances::<T>::get(pid) == PoolType::None,
+			Error::<T>::PoolAlreadyExists
+		);
+		Ok(())
+	}
+
+	fn on_pool_registered(pid: &PoolId, curve: PoolCurve) {
+		PoolInstances::<T>::insert(pid.clone(), Self::get_pool_type_of_curve(curve));
+	}
+
+	/// Only forgets the pid while it still names an amm pool
+	fn on_pool_unregistered(pid: &PoolId) {
+		match PoolInstances::<T>::get(pid) {
+			PoolType::AmmPool | PoolType::StablePool | PoolType::WeightedPool => PoolInstances::<T>::remove(pid),
+			PoolType::OrderBook | PoolType::None => (),
+		}
+	}
+}
@@ -0,0 +1,314 @@
+// This file is part of PolkaExchange.
+
+// Copyright (C) 2020-2021 John.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+mod tests {
+    use crate::mock::*;
+    use crate::Error;
+    use frame_support::{assert_noop, assert_ok};
+	use sp_std::collections::btree_map::BTreeMap;
+
+	use base::*;
+	use pallet_pool_amm::WeightData;
+	use pallet_order_book::OrderSide;
+	use sp_runtime::{FixedPointNumber, FixedU128};
+
+	fn create_pool(pid: &PoolId, symbol1: &AssetSymbol, balance1: Balance, symbol2: &AssetSymbol, balance2: Balance) {
+		let mut symbol_data: SymbolData = BTreeMap::new();
+		symbol_data.insert(symbol1.clone(), balance1);
+		symbol_data.insert(symbol2.clone(), balance2);
+
+		assert_ok!(PoolManager::create_amm_pool(
+			&ALICE,
+			pid,
+			30,
+			10,
+			&symbol_data,
+			None,
+			)
+		);
+	}
+
+	fn create_pools() {
+		create_pool(&POOL1, &TEST_SYMBOL1, 10_000, &TEST_SYMBOL2, 10_000);
+		create_pool(&POOL2, &TEST_SYMBOL2, 10_000, &TEST_SYMBOL3, 20_000);
+	}
+
+	#[test]
+	fn test_swap_with_path() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pools();
+
+			let path: SwapPath = vec![(POOL1, TEST_SYMBOL2), (POOL2, TEST_SYMBOL3)];
+			let first_hop = PoolManager::get_swap_return_asset_from_pool(&POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2);
+			let second_hop = PoolManager::get_swap_return_asset_from_pool(&POOL2, &TEST_SYMBOL2, first_hop, &TEST_SYMBOL3);
+			let expected_amount_out = PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL1, 100, &path)
+				.expect("Failed to quote path.");
+			assert_eq!(expected_amount_out, second_hop);
+
+			assert_ok!(PoolManager::swap_with_path(
+				&BOB,
+				&TEST_SYMBOL1,
+				100,
+				&path,
+				expected_amount_out,
+				None,
+				)
+			);
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &BOB), Ok(INITIAL_BALANCE - 100));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL3, &BOB), Ok(INITIAL_BALANCE + expected_amount_out));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL1));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL2));
+		})
+	}
+
+	#[test]
+	fn test_swap_with_path_is_atomic() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pools();
+
+			let path: SwapPath = vec![(POOL1, TEST_SYMBOL2), (POOL2, TEST_SYMBOL3)];
+			let expected_amount_out = PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL1, 100, &path)
+				.expect("Failed to quote path.");
+
+			assert_noop!(PoolManager::swap_with_path(
+					&BOB,
+					&TEST_SYMBOL1,
+					100,
+					&path,
+					expected_amount_out + 1,
+					None,
+				),
+				Error::<Runtime>::NoEnoughSwapAmount
+			);
+
+			let invalid_path: SwapPath = vec![(POOL1, TEST_SYMBOL2), (POOL1, TEST_SYMBOL3)];
+			assert_noop!(PoolManager::swap_with_path(
+					&BOB,
+					&TEST_SYMBOL1,
+					100,
+					&invalid_path,
+					1,
+					None,
+				),
+				Error::<Runtime>::InvalidSwapPath
+			);
+			assert_eq!(
+				PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL1, 100, &SwapPath::new()),
+				Err(Error::<Runtime>::EmptySwapPath.into())
+			);
+
+			// the first hop moves the price of its pair by about 2%
+			assert_noop!(PoolManager::swap_path(Origin::signed(BOB), TEST_SYMBOL1, 100, path.clone(), 1, None, Some(100)),
+				pallet_pool_amm::Error::<Runtime>::TooLargePriceImpact
+			);
+			assert_ok!(PoolManager::swap_path(Origin::signed(BOB), TEST_SYMBOL1, 100, path, 1, None, Some(500)));
+		})
+	}
+
+	#[test]
+	fn test_stable_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pools();
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 10_000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 10_000);
+			assert_noop!(PoolManager::create_stable_pool(&ALICE, &POOL1, 100, 4, 0, &symbol_data, None),
+				Error::<Runtime>::PoolAlreadyExists
+			);
+			assert_ok!(PoolManager::create_stable_pool(&ALICE, &POOL3, 100, 4, 0, &symbol_data, None));
+			assert_eq!(PoolManager::pool_instances(&POOL3), PoolType::StablePool);
+			assert!(PoolManager::is_swappable_in_pool(&POOL3, &TEST_SYMBOL1, &TEST_SYMBOL2));
+
+			let stable_amount_out = PoolManager::get_swap_return_asset_from_pool(&POOL3, &TEST_SYMBOL1, 1000, &TEST_SYMBOL2);
+			let amm_amount_out = PoolManager::get_swap_return_asset_from_pool(&POOL1, &TEST_SYMBOL1, 1000, &TEST_SYMBOL2);
+			assert!(stable_amount_out > amm_amount_out);
+
+			assert_ok!(PoolManager::swap_asset_in_pool(&BOB, &POOL3, &TEST_SYMBOL1, 1000, &TEST_SYMBOL2, stable_amount_out));
+			assert_eq!(PoolManager::get_volume_data_from_pool(&POOL3).get(&TEST_SYMBOL1).unwrap().input, 1000);
+			assert_eq!(PoolManager::get_total_fee_from_pool(&POOL3), 4);
+
+			let shares = PoolManager::share_balance_of_pool(&ALICE, &POOL3);
+			assert_eq!(shares, PoolManager::share_total_balance_from_pool(&POOL3));
+			assert_ok!(PoolManager::remove_liquidity_from_pool(&ALICE, &POOL3, shares, &SymbolData::new()));
+			assert_ok!(PoolManager::destroy_pool(&ALICE, &POOL3));
+		})
+	}
+
+	#[test]
+	fn test_weighted_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 20_000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 10_000);
+			symbol_data.insert(TEST_SYMBOL3.clone(), 10_000);
+			let mut weights: WeightData = BTreeMap::new();
+			weights.insert(TEST_SYMBOL1.clone(), 2);
+			weights.insert(TEST_SYMBOL2.clone(), 1);
+			weights.insert(TEST_SYMBOL3.clone(), 1);
+			assert_ok!(PoolManager::create_weighted_pool(&ALICE, &POOL3, &weights, 30, 0, &symbol_data, None));
+			assert_eq!(PoolManager::pool_instances(&POOL3), PoolType::WeightedPool);
+
+			let amount_out = PoolManager::get_swap_return_asset_from_pool(&POOL3, &TEST_SYMBOL2, 1000, &TEST_SYMBOL3);
+			assert_ok!(PoolManager::swap_asset_in_pool(&BOB, &POOL3, &TEST_SYMBOL2, 1000, &TEST_SYMBOL3, amount_out));
+			assert_ok!(PoolManager::add_single_asset_liquidity_to_pool(&BOB, &POOL3, &TEST_SYMBOL1, 1000, 1));
+			assert!(PoolManager::share_balance_of_pool(&BOB, &POOL3) > 0);
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL3));
+
+			// routes do not go through the same pool twice
+			assert_eq!(PoolManager::find_routes(&TEST_SYMBOL2, 1000, &TEST_SYMBOL3, 3).len(), 1);
+		})
+	}
+
+	#[test]
+	fn test_calls() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 10_000);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 10_000);
+			assert_ok!(PoolManager::create_amm(Origin::signed(ALICE), POOL1, 30, 10, symbol_data.clone(), None));
+			assert_eq!(PoolManager::pool_instances(&POOL1), PoolType::AmmPool);
+			assert_noop!(PoolManager::create_amm(Origin::signed(BOB), POOL1, 30, 10, symbol_data.clone(), None),
+				Error::<Runtime>::PoolAlreadyExists
+			);
+
+			assert_ok!(PoolManager::add_liquidity(Origin::signed(BOB), POOL1, symbol_data, None, None));
+			let expected_amount_out = PoolManager::get_swap_return_asset_from_pool(&POOL1, &TEST_SYMBOL1, 100, &TEST_SYMBOL2);
+			assert_ok!(PoolManager::swap(Origin::signed(BOB), POOL1, TEST_SYMBOL1, 100, TEST_SYMBOL2, expected_amount_out, None, None));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL2, &BOB), Ok(INITIAL_BALANCE - 10_000 + expected_amount_out));
+
+			let path: SwapPath = vec![(POOL1, TEST_SYMBOL1)];
+			assert_ok!(PoolManager::swap_path(Origin::signed(BOB), TEST_SYMBOL2, 100, path, 1, None, None));
+
+			let shares = PoolManager::share_balance_of_pool(&BOB, &POOL1);
+			assert_ok!(PoolManager::remove_liquidity(Origin::signed(BOB), POOL1, shares, SymbolData::new(), None, None));
+			let shares = PoolManager::share_balance_of_pool(&ALICE, &POOL1);
+			assert_ok!(PoolManager::remove_liquidity(Origin::signed(ALICE), POOL1, shares, SymbolData::new(), None, None));
+			assert_ok!(PoolAmm::ensure_reserves_consistent(&POOL1));
+
+			assert_ok!(PoolManager::destroy(Origin::signed(ALICE), POOL1));
+			assert_eq!(PoolManager::pool_instances(&POOL1), PoolType::None);
+		})
+	}
+
+	#[test]
+	fn test_pool_instances_follow_pool_amm() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			let mut symbol_data: SymbolData = BTreeMap::new();
+			symbol_data.insert(TEST_SYMBOL1.clone(), 0);
+			symbol_data.insert(TEST_SYMBOL2.clone(), 0);
+			assert_ok!(PoolAmm::register_pool(&ALICE, &POOL2, 30, 10, &symbol_data, None));
+			assert_eq!(PoolManager::pool_instances(&POOL2), PoolType::AmmPool);
+			assert!(PoolManager::is_swappable_in_pool(&POOL2, &TEST_SYMBOL1, &TEST_SYMBOL2) == false);
+
+			assert_ok!(PoolAmm::unregister_pool(&ALICE, &POOL2));
+			assert_eq!(PoolManager::pool_instances(&POOL2), PoolType::None);
+
+			// a pid taken by an order book can not be registered in pool-amm behind its back
+			assert_ok!(PoolManager::create_order_book_pool(&ALICE, &POOL3, &TEST_SYMBOL1, &TEST_SYMBOL3));
+			assert_noop!(PoolAmm::register_pool(&ALICE, &POOL3, 30, 10, &symbol_data, None),
+				Error::<Runtime>::PoolAlreadyExists
+			);
+			assert_eq!(PoolManager::pool_instances(&POOL3), PoolType::OrderBook);
+		})
+	}
+
+	#[test]
+	fn test_register_existing_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			assert_noop!(PoolManager::register_existing_pool(&POOL1, &PoolType::AmmPool),
+				pallet_pool_amm::Error::<Runtime>::PoolNotExists
+			);
+
+			create_pool(&POOL1, &TEST_SYMBOL1, 10_000, &TEST_SYMBOL2, 10_000);
+			crate::PoolInstances::<Runtime>::remove(&POOL1);
+			assert_noop!(PoolManager::register_existing_pool(&POOL1, &PoolType::StablePool),
+				Error::<Runtime>::PoolTypeMismatch
+			);
+			assert_noop!(PoolManager::register_existing_pool(&POOL1, &PoolType::None),
+				Error::<Runtime>::PoolNotExists
+			);
+			assert_ok!(PoolManager::register_existing_pool(&POOL1, &PoolType::AmmPool));
+			assert_eq!(PoolManager::pool_instances(&POOL1), PoolType::AmmPool);
+		})
+	}
+
+	#[test]
+	fn test_find_best_route() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pools();
+			create_pool(&POOL3, &TEST_SYMBOL1, 1_000, &TEST_SYMBOL3, 2_000);
+
+			let two_hops: SwapPath = vec![(POOL1, TEST_SYMBOL2), (POOL2, TEST_SYMBOL3)];
+			let direct: SwapPath = vec![(POOL3, TEST_SYMBOL3)];
+			let two_hops_amount_out = PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL1, 500, &two_hops).unwrap();
+			let direct_amount_out = PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL1, 500, &direct).unwrap();
+			assert!(two_hops_amount_out > direct_amount_out);
+
+			assert_eq!(PoolManager::find_routes(&TEST_SYMBOL1, 500, &TEST_SYMBOL3, 2).len(), 2);
+			assert_eq!(PoolManager::find_best_route(&TEST_SYMBOL1, 500, &TEST_SYMBOL3, 2), Some((two_hops.clone(), two_hops_amount_out)));
+			assert_eq!(PoolManager::find_best_route(&TEST_SYMBOL1, 500, &TEST_SYMBOL3, 1), Some((direct.clone(), direct_amount_out)));
+			assert_eq!(PoolManager::find_best_route(&TEST_SYMBOL1, 500, &DEFAULT_SYMBOL, 3), None);
+
+			let split = PoolManager::find_best_split_route(&TEST_SYMBOL1, 500, &TEST_SYMBOL3, 2, 10);
+			assert_eq!(split.len(), 2);
+			assert_eq!(split.iter().map(|(_, amount_in, _)| *amount_in).sum::<Balance>(), 500);
+			assert!(split.iter().map(|(_, _, amount_out)| *amount_out).sum::<Balance>() > two_hops_amount_out);
+
+			// the parts are capped, the input is still split in full
+			let split_capped = PoolManager::find_best_split_route(&TEST_SYMBOL1, 500, &TEST_SYMBOL3, 2, u32::max_value());
+			assert_eq!(split_capped.iter().map(|(_, amount_in, _)| *amount_in).sum::<Balance>(), 500);
+			assert!(split_capped.len() <= 2);
+
+			for (path, amount_in, amount_out) in split {
+				assert_ok!(PoolManager::swap_with_path(&BOB, &TEST_SYMBOL1, amount_in, &path, amount_out, None));
+			}
+		})
+	}
+
+	#[test]
+	fn test_order_book_pool() {
+		let mut ext = ExtBuilder::default().build();
+		ext.execute_with(|| {
+			create_pool(&POOL1, &TEST_SYMBOL1, 10_000, &TEST_SYMBOL2, 10_000);
+			assert_ok!(PoolManager::create_order_book(Origin::signed(ALICE), POOL3, TEST_SYMBOL1, TEST_SYMBOL3));
+			assert_eq!(PoolManager::pool_instances(&POOL3), PoolType::OrderBook);
+			assert_noop!(PoolManager::create_order_book(Origin::signed(BOB), POOL1, TEST_SYMBOL1, TEST_SYMBOL3),
+				Error::<Runtime>::PoolAlreadyExists
+			);
+			assert_noop!(PoolManager::add_liquidity(Origin::signed(BOB), POOL3, SymbolData::new(), None, None),
+				Error::<Runtime>::UnsupportedPoolType
+			);
+			assert_ok!(OrderBook::place_limit_order(&ALICE, &POOL3, OrderSide::Bid, FixedU128::saturating_from_integer(2), 1_000));
+
+			// the route sells through the amm pool, then to the bid
+			let path: SwapPath = vec![(POOL1, TEST_SYMBOL1), (POOL3, TEST_SYMBOL3)];
+			let amount_out = PoolManager::get_swap_return_asset_with_path(&TEST_SYMBOL2, 100, &path).unwrap();
+			let first_hop = PoolManager::get_swap_return_asset_from_pool(&POOL1, &TEST_SYMBOL2, 100, &TEST_SYMBOL1);
+			assert_eq!(amount_out, first_hop * 2);
+			assert_eq!(PoolManager::find_best_route(&TEST_SYMBOL2, 100, &TEST_SYMBOL3, 2), Some((path.clone(), amount_out)));
+
+			assert_ok!(PoolManager::swap_path(Origin::signed(BOB), TEST_SYMBOL2, 100, path, amount_out, None, None));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL3, &BOB), Ok(INITIAL_BALANCE + amount_out));
+			assert_eq!(FungibleAsset::free_balance(&TEST_SYMBOL1, &ALICE), Ok(INITIAL_BALANCE - 10_000 + first_hop));
+			assert_eq!(PoolManager::get_volume_data_from_pool(&POOL3).get(&TEST_SYMBOL1).map(|volume| volume.input), Some(first_hop));
+
+			assert_noop!(PoolManager::destroy(Origin::signed(ALICE), POOL3),
+				pallet_order_book::Error::<Runtime>::HasOpenOrders
+			);
+			assert_ok!(OrderBook::cancel_limit_order(&ALICE, &POOL3, 0));
+			assert_ok!(PoolManager::destroy(Origin::signed(ALICE), POOL3));
+			assert_eq!(PoolManager::pool_instances(&POOL3), PoolType::None);
+		})
+	}
+}
@@ -14,11 +14,11 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use sp_std::prelude::*;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
 	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
-	AccountIdLookup, BlakeTwo256, Block as BlockT, Verify, IdentifyAccount, NumberFor,
+	AccountIdLookup, AccountIdConversion, BlakeTwo256, Block as BlockT, Verify, IdentifyAccount, NumberFor,
 };
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -36,7 +36,7 @@ pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Filter, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -52,7 +52,10 @@ pub use base::*;
 /// Import the pallets.
 pub use pallet_fungible_asset;
 pub use pallet_pool_amm;
+pub use pallet_order_book;
+pub use pallet_limit_order;
 pub use pallet_pool_manager;
+pub use pallet_dca;
 
 pub use pallet_nft;
 pub use pallet_farming;
@@ -157,11 +160,24 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+/// Non native assets are only transferred through `FungibleAsset`, `Currencies::transfer` would move them past the freezes.
+/// Flash swaps stay off until the runtime has a handler for them, without one they are only swaps paid afterwards.
+pub struct BaseFilter;
+impl Filter<Call> for BaseFilter {
+	fn filter(call: &Call) -> bool {
+		match call {
+			Call::Currencies(currencies::Call::transfer(_, currency_id, _)) => *currency_id == GetNativeCurrencyId::get(),
+			Call::PoolAmm(pallet_pool_amm::Call::flash_swap(..)) => false,
+			_ => true,
+		}
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = ();
+	type BaseCallFilter = BaseFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -304,21 +320,77 @@ impl tokens::Config for Runtime {
     type OnDust = ();
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 10_000_000_000_000_000;
+}
+
 impl pallet_fungible_asset::Config for Runtime {
 	type Event = Event;
 	type AssetId = AssetId;
 	type Currency = currencies::Module<Runtime>;
+	type NativeAssetId = GetNativeCurrencyId;
+	type AssetDeposit = AssetDeposit;
+}
+
+parameter_types! {
+	pub const PoolAmmModuleId: ModuleId = ModuleId(*b"pex/pool");
+	pub const ProtocolFeeModuleId: ModuleId = ModuleId(*b"pex/fees");
+	pub ProtocolFeeAccount: AccountId = ProtocolFeeModuleId::get().into_account();
 }
 
 impl pallet_pool_amm::Config for Runtime {
 	type Event = Event;
 	type PoolId = PoolId;
+	type ModuleId = PoolAmmModuleId;
+	type ProtocolFeeRecipient = ProtocolFeeAccount;
+	/// `flash_swap` is filtered out by `BaseFilter` while there is no handler
+	type FlashSwapHandler = ();
+	type PoolLifecycleHandler = PoolManager;
+}
+
+parameter_types! {
+	pub const MaxBookOrdersPerAccount: u32 = 32;
+	pub const OrderDeposit: Balance = 1_000_000_000_000_000;
+}
+
+impl pallet_order_book::Config for Runtime {
+	type Event = Event;
+	type MaxOrdersPerAccount = MaxBookOrdersPerAccount;
+	type OrderDeposit = OrderDeposit;
+}
+
+parameter_types! {
+	pub const MaxOpenLimitOrders: u32 = 256;
+	pub const MaxLimitOrdersPerAccount: u32 = 16;
+	pub const LimitOrderDeposit: Balance = 1_000_000_000_000_000;
+	pub const MaxLimitOrderFillsPerBlock: u32 = 32;
+}
+
+impl pallet_limit_order::Config for Runtime {
+	type Event = Event;
+	type MaxOpenOrders = MaxOpenLimitOrders;
+	type MaxOrdersPerAccount = MaxLimitOrdersPerAccount;
+	type OrderDeposit = LimitOrderDeposit;
+	type MaxFillAttemptsPerBlock = MaxLimitOrderFillsPerBlock;
 }
 
 impl pallet_pool_manager::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const MaxSchedulesPerBlock: u32 = 64;
+	pub const MaxFailedInstalments: u32 = 5;
+	pub const ScheduleDeposit: Balance = 1_000_000_000_000_000;
+}
+
+impl pallet_dca::Config for Runtime {
+	type Event = Event;
+	type MaxSchedulesPerBlock = MaxSchedulesPerBlock;
+	type MaxFailedInstalments = MaxFailedInstalments;
+	type ScheduleDeposit = ScheduleDeposit;
+}
+
 impl pallet_nft::Config for Runtime {
 	type Event = Event;
 	type Data = StdString;
@@ -348,7 +420,10 @@ construct_runtime!(
 		Tokens: tokens::{Module, Storage, Config<T>, Event<T>},
 		FungibleAsset: pallet_fungible_asset::{Module, Call, Storage, Config<T>, Event<T>},
 		PoolAmm: pallet_pool_amm::{Module, Call, Storage, Config<T>, Event<T>},
+		OrderBook: pallet_order_book::{Module, Call, Storage, Event<T>},
+		LimitOrder: pallet_limit_order::{Module, Call, Storage, Event<T>},
 		PoolManager: pallet_pool_manager::{Module, Call, Storage, Config<T>, Event<T>},
+		Dca: pallet_dca::{Module, Call, Storage, Event<T>},
 		NFT: pallet_nft::{Module, Call, Storage, Config<T>, Event<T>},
 		Farming: pallet_farming::{Module, Call, Storage, Config<T>, Event<T>},
 	}
@@ -519,6 +594,35 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_pool_manager_runtime_api::PoolManagerApi<Block> for Runtime {
+		fn get_swap_return_with_path(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			path: SwapPath,
+		) -> Option<Balance> {
+			PoolManager::get_swap_return_asset_with_path(&asset_in, amount_in, &path).ok()
+		}
+
+		fn find_best_route(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			max_hops: u32,
+		) -> Option<(SwapPath, Balance)> {
+			PoolManager::find_best_route(&asset_in, amount_in, &asset_out, max_hops)
+		}
+
+		fn find_best_split_route(
+			asset_in: AssetSymbol,
+			amount_in: Balance,
+			asset_out: AssetSymbol,
+			max_hops: u32,
+			parts: u32,
+		) -> Vec<(SwapPath, Balance, Balance)> {
+			PoolManager::find_best_split_route(&asset_in, amount_in, &asset_out, max_hops, parts)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(